@@ -1,27 +1,267 @@
 #![allow(dead_code)]
+use std::env;
+use std::io::{self, BufRead, Write};
+
+use hanabi::agent::Agent;
+use hanabi::bots::{RandomBot, SafeBot};
+use hanabi::card::{Card, CardKnowledge};
 use hanabi::errors::*;
 use hanabi::game::*;
+use hanabi::moves::{Action, ActionKind, HanabiMove};
+use hanabi::player::PubID;
 use hanabi::rules::*;
+use hanabi::sim::{simulate_parallel, SimulationReport};
+use hanabi::view::PlayerView;
+
+const DEFAULT_PLAYERS: usize = 3;
+const DEFAULT_GAMES: usize = 1000;
+const USAGE: &str =
+    "Usage: main [players]\n       main sim <random|safe> [players] [games] [first seed] [threads]";
+// ANSI escape: clear the screen and move the cursor back to the top left
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+
+fn cards_to_string(cards: &[Card]) -> String {
+    if cards.is_empty() {
+        return "-".to_string();
+    }
+
+    cards
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/**
+ * @brief Show what a player knows about one of their cards, e.g. "[rb 1]" for a red or blue one
+ */
+fn knowledge_to_string(knowledge: &CardKnowledge) -> String {
+    let colors: String = knowledge
+        .possible_colors()
+        .iter()
+        .map(|c| c.letter())
+        .collect();
+    let numbers: String = knowledge
+        .possible_numbers()
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+
+    format!("[{} {}]", colors, numbers)
+}
+
+fn action_to_string(action: &Action) -> String {
+    let what = match &action.kind {
+        ActionKind::Play { card, success, .. } => {
+            if *success {
+                format!("played {}", card)
+            } else {
+                format!("misplayed {} and got a strike", card)
+            }
+        }
+        ActionKind::Discard { card, .. } => format!("discarded {}", card),
+        ActionKind::Hint {
+            target,
+            hint,
+            touched,
+        } => format!(
+            "told player {} about their {} (slots {:?})",
+            target, hint, touched
+        ),
+    };
+
+    match action.drawn() {
+        Some(card) => format!("Player {} {} and drew {}", action.player, what, card),
+        None => format!("Player {} {}", action.player, what),
+    }
+}
+
+fn print_view(view: &PlayerView) {
+    println!();
+    println!("===== Player {}'s turn =====", view.public_id);
+    println!(
+        "Hints: {}/{}  Strikes: {}/{}  Deck: {}",
+        view.hints, view.config.max_hints, view.strikes, view.config.max_strikes, view.deck_len
+    );
+    println!("Board:   {}", cards_to_string(&view.board));
+    println!("Discard: {}", cards_to_string(&view.discard));
+    if let Some(action) = view.log.last() {
+        println!("Last:    {}", action_to_string(action));
+    }
+    println!();
+
+    for other in &view.others {
+        let cards: Vec<String> = other
+            .cards
+            .iter()
+            .zip(&other.knowledge)
+            .map(|(card, knowledge)| format!("{}{}", card, knowledge_to_string(knowledge)))
+            .collect();
+        println!("Player {}: {}", other.public_id, cards.join(" "));
+    }
+
+    let mine: Vec<String> = view
+        .my_knowledge
+        .iter()
+        .enumerate()
+        .map(|(idx, knowledge)| format!("{}:{}", idx, knowledge_to_string(knowledge)))
+        .collect();
+    println!("You:      {}", mine.join(" "));
+}
+
+/**
+ * @brief Read one line from the terminal, or None once input has run out
+ */
+fn read_line(input: &mut impl BufRead, prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    io::stdout().flush().ok()?;
+
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
 
-pub fn play() -> Result<(), HanabiError> {
+pub fn play(num_players: usize) -> Result<(), HanabiError> {
+    let config = GameConfig::standard(num_players)?;
     let deck = generate_normal_deck();
-    let _g = Game::new(3, deck)?;
+    let mut g = Game::new(config, deck)?;
 
-    //println!("{:?}", g.player_hands[0]);
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
 
-    //comms::init_player_interfaces();
+    println!("Moves: 'play <slot>', 'discard <slot>', 'clue <player> <color|number>'");
 
-    //comms::send_players_initial_state(&g);
+    while let GameResultState::InProgress = g.finished() {
+        let active: PubID = g.active_player();
+        let uid = g.uid_of(active)?;
 
-    //while !g.finished() {
-    //comms::get_move()
-    //}
+        // Don't show the next player's view to whoever is still looking at the screen, and clear
+        // this player's view (with everyone else's hands on it) before passing the terminal on
+        let prompt = format!("\nPass the terminal to player {} and press Enter", active);
+        if read_line(&mut input, &prompt).is_none() {
+            return Ok(());
+        }
+        print_view(&g.view_for(uid)?);
+
+        loop {
+            let line = match read_line(&mut input, "> ") {
+                Some(line) => line,
+                None => return Ok(()),
+            };
+
+            let result = line
+                .parse::<HanabiMove>()
+                .and_then(|mv| g.play_move(mv, uid));
+            match result {
+                Ok(action) => {
+                    print!("{}", CLEAR_SCREEN);
+                    println!("{}", action_to_string(&action.seen_by(active)));
+                    break;
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
+
+    if let GameResultState::Finished(reason, score) = g.finished() {
+        println!();
+        println!("Game over ({:?}). Final score: {}", reason, score);
+    }
 
     Ok(())
 }
 
-pub fn main() -> Result<(), HanabiError> {
-    play()?;
+/**
+ * @brief A table of bots of one kind for the game dealt from the given seed
+ */
+fn make_table(bot: &str, num_players: usize, seed: u64) -> Option<Vec<Box<dyn Agent>>> {
+    (0..num_players as u64)
+        .map(|p| -> Option<Box<dyn Agent>> {
+            match bot {
+                "random" => Some(Box::new(RandomBot::new(
+                    seed.wrapping_mul(num_players as u64).wrapping_add(p),
+                ))),
+                "safe" => Some(Box::new(SafeBot::new())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn print_report(report: &SimulationReport) {
+    println!("Games:     {}", report.games.len());
+    println!("Mean:      {:.3}", report.mean);
+    println!("Median:    {:.1}", report.median);
+    println!("Std dev:   {:.3}", report.stddev);
+    println!("Perfect:   {:.2}%", 100.0 * report.perfect_rate);
+    println!("Strikeout: {:.2}%", 100.0 * report.strikeout_rate);
+    if let Some(worst) = report.worst() {
+        println!("Worst:     {} (seed {})", worst.score, worst.seed);
+    }
+    println!();
+
+    let most = report.histogram.iter().max().copied().unwrap_or(0).max(1);
+    for (score, &count) in report.histogram.iter().enumerate() {
+        let bar = "#".repeat((count * 50).div_ceil(most));
+        println!("{:>3} {:>7} {}", score, count, bar);
+    }
+}
+
+/**
+ * @brief Parse the nth argument, falling back to a default if it wasn't given
+ */
+fn arg_or<T: std::str::FromStr>(args: &[String], n: usize, default: T) -> Option<T> {
+    match args.get(n) {
+        Some(arg) => arg.parse().ok(),
+        None => Some(default),
+    }
+}
+
+pub fn run_simulation(args: &[String]) -> Result<(), HanabiError> {
+    let bot = args.first().map(String::as_str).unwrap_or("");
+    let parsed = (
+        arg_or(args, 1, DEFAULT_PLAYERS),
+        arg_or(args, 2, DEFAULT_GAMES),
+        arg_or(args, 3, 0u64),
+        arg_or(args, 4, 0usize),
+    );
+    let (num_players, num_games, first_seed, num_threads) = match parsed {
+        (Some(players), Some(games), Some(seed), Some(threads))
+            if make_table(bot, 1, 0).is_some() =>
+        {
+            (players, games, seed, threads)
+        }
+        _ => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+
+    let config = GameConfig::standard(num_players)?;
+    let report = simulate_parallel(&config, num_games, first_seed, num_threads, |seed| {
+        make_table(bot, num_players, seed).unwrap_or_default()
+    })?;
+
+    println!("{} bots, {} players", bot, num_players);
+    print_report(&report);
 
     Ok(())
 }
+
+pub fn main() -> Result<(), HanabiError> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.first().map(String::as_str) == Some("sim") {
+        return run_simulation(&args[1..]);
+    }
+
+    match arg_or(&args, 0, DEFAULT_PLAYERS) {
+        Some(num_players) => play(num_players),
+        None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    }
+}
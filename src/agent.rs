@@ -0,0 +1,172 @@
+use crate::errors::HanabiError;
+use crate::game::Game;
+use crate::moves::{Action, HanabiMove};
+use crate::player::PubID;
+use crate::rules::{GameConfig, GameOverReason, GameResultState};
+use crate::view::PlayerView;
+
+/**
+ * @brief Anything that can sit at the table and decide moves: a bot, a network client, a human
+ * behind a UI. Agents only ever see a PlayerView, never the Game itself.
+ */
+pub trait Agent {
+    /**
+     * @brief Pick a move. Only called when it is this agent's turn.
+     */
+    fn choose_move(&mut self, view: &PlayerView) -> HanabiMove;
+
+    /**
+     * @brief Called after every action in the game, including this agent's own, redacted so
+     * that the agent never sees the cards it drew.
+     */
+    fn notify(&mut self, _action: &Action) {}
+}
+
+/**
+ * @brief How a game played by agents turned out
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub reason: GameOverReason,
+    pub score: usize,
+    pub log: Vec<Action>,
+}
+
+/**
+ * @brief Play a new game with a freshly shuffled deck of the cards the config calls for.
+ * agents[i] plays as player i.
+ */
+pub fn run_game<A: Agent + ?Sized>(
+    config: GameConfig,
+    agents: &mut [Box<A>],
+) -> Result<GameRecord, HanabiError> {
+    let deck = config.generate_deck();
+    let game = Game::new(config, deck)?;
+    play_out(game, agents)
+}
+
+/**
+ * @brief Drive an existing game to completion, asking agents[i] for player i's moves. Fails as
+ * soon as an agent makes an illegal move.
+ */
+pub fn play_out<A: Agent + ?Sized>(
+    mut game: Game,
+    agents: &mut [Box<A>],
+) -> Result<GameRecord, HanabiError> {
+    if agents.len() != game.config().num_players {
+        return Err(HanabiError::WrongNumberOfAgents {
+            agents: agents.len(),
+            num_players: game.config().num_players,
+        });
+    }
+
+    loop {
+        if let GameResultState::Finished(reason, score) = game.finished() {
+            return Ok(GameRecord {
+                reason,
+                score,
+                log: game.log().to_vec(),
+            });
+        }
+
+        let active = game.active_player();
+        let uid = game.uid_of(active)?;
+
+        let view = game.view_for(uid)?;
+        let mv = agents[active as usize].choose_move(&view);
+        let action = game.play_move(mv, uid)?;
+
+        for (pub_id, agent) in agents.iter_mut().enumerate() {
+            agent.notify(&action.seen_by(pub_id as PubID));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::{ActionKind, Hint};
+    use crate::rules::Variant;
+
+    /**
+     * @brief Discards its oldest card whenever it can, otherwise hints the next player's color
+     */
+    struct Discarder {
+        seen: Vec<Action>,
+    }
+
+    impl Agent for Discarder {
+        fn choose_move(&mut self, view: &PlayerView) -> HanabiMove {
+            if view.hints < view.config.max_hints {
+                return HanabiMove::Discard(0);
+            }
+
+            let next = &view.others[0];
+            HanabiMove::Hint((next.public_id, Hint::ColorHint(next.cards[0].color())))
+        }
+
+        fn notify(&mut self, action: &Action) {
+            self.seen.push(action.clone());
+        }
+    }
+
+    #[test]
+    fn test_run_game() {
+        let mut agents: Vec<Box<Discarder>> = (0..3)
+            .map(|_| Box::new(Discarder { seen: Vec::new() }))
+            .collect();
+
+        let record = run_game(GameConfig::standard(3).unwrap(), &mut agents).unwrap();
+
+        assert_eq!(record.reason, GameOverReason::DeckExhausted);
+        assert_eq!(record.score, 0);
+
+        // Every agent heard about every action, but never saw its own draws
+        for (pub_id, agent) in agents.iter().enumerate() {
+            assert_eq!(agent.seen.len(), record.log.len());
+            for (seen, action) in agent.seen.iter().zip(&record.log) {
+                assert_eq!(seen, &action.seen_by(pub_id as PubID));
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_game_custom_config() {
+        let mut config = GameConfig::standard(2).unwrap();
+        config.variant = Variant::Black;
+        config.card_frequencies = [1, 1, 1, 1, 1];
+        let mut agents: Vec<Box<Discarder>> = (0..2)
+            .map(|_| Box::new(Discarder { seen: Vec::new() }))
+            .collect();
+
+        let record = run_game(config.clone(), &mut agents).unwrap();
+
+        // Everything left after the deal gets drawn: one copy of each card in six suits
+        let drawn: Vec<_> = record
+            .log
+            .iter()
+            .filter_map(|action| match action.kind {
+                ActionKind::Play { drawn, .. } | ActionKind::Discard { drawn, .. } => drawn,
+                ActionKind::Hint { .. } => None,
+            })
+            .collect();
+        assert_eq!(drawn.len(), config.deck_size() - 2 * config.hand_size);
+        assert_eq!(drawn.len(), 20);
+        for (i, card) in drawn.iter().enumerate() {
+            assert!(!drawn[i + 1..].contains(card));
+        }
+    }
+
+    #[test]
+    fn test_wrong_number_of_agents() {
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(Discarder { seen: Vec::new() })];
+
+        assert!(matches!(
+            run_game(GameConfig::standard(2).unwrap(), &mut agents),
+            Err(HanabiError::WrongNumberOfAgents {
+                agents: 1,
+                num_players: 2
+            })
+        ));
+    }
+}
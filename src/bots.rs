@@ -0,0 +1,343 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::agent::Agent;
+use crate::card::{CardKnowledge, Color};
+use crate::moves::{ActionKind, HanabiMove, Hint};
+use crate::rules::playable_on;
+use crate::view::PlayerView;
+
+/**
+ * @brief Picks uniformly among the legal moves. Seeded, so a game between RandomBots is
+ * reproducible.
+ */
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        RandomBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Agent for RandomBot {
+    fn choose_move(&mut self, view: &PlayerView) -> HanabiMove {
+        *view
+            .legal_moves()
+            .choose(&mut self.rng)
+            .unwrap_or(&HanabiMove::Play(0))
+    }
+}
+
+/**
+ * @brief Simple rule-based bot that never risks a strike. In order of preference it:
+ *  1.) plays a card its knowledge proves playable
+ *  2.) hints a playable card to the first player after it that holds one
+ *  3.) discards its oldest card that hasn't been hinted
+ *  4.) gives any hint at all, rather than play blind
+ */
+#[derive(Default)]
+pub struct SafeBot;
+
+impl SafeBot {
+    pub fn new() -> Self {
+        SafeBot
+    }
+
+    fn safe_play(view: &PlayerView) -> Option<HanabiMove> {
+        view.my_knowledge
+            .iter()
+            .position(|knowledge| known_playable(view, knowledge))
+            .map(HanabiMove::Play)
+    }
+
+    fn helpful_hint(view: &PlayerView) -> Option<HanabiMove> {
+        if view.hints == 0 {
+            return None;
+        }
+
+        // Players in turn order, starting with the one right after us
+        let num_players = view.config.num_players;
+        let mut others: Vec<_> = view.others.iter().collect();
+        others.sort_by_key(|other| {
+            (other.public_id as usize + num_players - view.public_id as usize) % num_players
+        });
+
+        for other in others {
+            for (card, knowledge) in other.cards.iter().zip(&other.knowledge) {
+                if !playable_on(&view.board, card.color(), card.number()) {
+                    continue;
+                }
+
+                // A color clue only helps if it rules out some suit the card could still be
+                let variant = view.config.variant;
+                let possible = knowledge.possible_colors();
+                let narrows = |clue: Color| {
+                    variant.color_touches(clue, card.color())
+                        && possible
+                            .iter()
+                            .any(|&suit| !variant.color_touches(clue, suit))
+                };
+
+                // Prefer naming the card's own color, but rainbow can only be reached through
+                // other colors
+                let clue_colors = variant.clue_colors();
+                let hint = if narrows(card.color()) && clue_colors.contains(&card.color()) {
+                    Hint::ColorHint(card.color())
+                } else if knowledge.possible_numbers().len() > 1 {
+                    Hint::NumberHint(card.number())
+                } else if let Some(&color) = clue_colors.iter().find(|&&clue| narrows(clue)) {
+                    Hint::ColorHint(color)
+                } else {
+                    continue;
+                };
+
+                return Some(HanabiMove::Hint((other.public_id, hint)));
+            }
+        }
+
+        None
+    }
+
+    fn oldest_discard(view: &PlayerView) -> Option<HanabiMove> {
+        if view.hints >= view.config.max_hints || view.hand_len() == 0 {
+            return None;
+        }
+
+        // New cards go on the end of the hand, so slot 0 is the oldest
+        let slot = clued_slots(view)
+            .iter()
+            .position(|&clued| !clued)
+            .unwrap_or(0);
+
+        Some(HanabiMove::Discard(slot))
+    }
+
+    /**
+     * @brief Burn a hint token when all of them are available but none of them is useful. Still
+     * better than risking a strike.
+     */
+    fn any_hint(view: &PlayerView) -> Option<HanabiMove> {
+        view.legal_moves()
+            .into_iter()
+            .find(|mv| matches!(mv, HanabiMove::Hint(_)))
+    }
+}
+
+impl Agent for SafeBot {
+    fn choose_move(&mut self, view: &PlayerView) -> HanabiMove {
+        SafeBot::safe_play(view)
+            .or_else(|| SafeBot::helpful_hint(view))
+            .or_else(|| SafeBot::oldest_discard(view))
+            .or_else(|| SafeBot::any_hint(view))
+            .unwrap_or(HanabiMove::Play(0))
+    }
+}
+
+/**
+ * @brief True if every card this knowledge allows for could be played right now
+ */
+fn known_playable(view: &PlayerView, knowledge: &CardKnowledge) -> bool {
+    let numbers = knowledge.possible_numbers();
+
+    knowledge.possible_colors().iter().all(|&color| {
+        numbers
+            .iter()
+            .all(|&number| playable_on(&view.board, color, number))
+    })
+}
+
+/**
+ * @brief Which cards in our hand a clue has touched. Knowledge alone can't tell: a red clue in
+ * the rainbow variant still leaves two suits, and negative clues can narrow a card down to one.
+ * So follow our current cards back through the log instead.
+ */
+fn clued_slots(view: &PlayerView) -> Vec<bool> {
+    let mut clued = vec![false; view.hand_len()];
+
+    // Every play and discard drew a card, up to and including the one that emptied the deck
+    let draws_until = match view.turns_since_last_pickup {
+        Some(turns) => view.log.len().saturating_sub(turns),
+        None => view.log.len(),
+    };
+
+    // slots[s] is where the card that was in slot s at that point sits now, if it's still here
+    let mut slots: Vec<Option<usize>> = (0..view.hand_len()).map(Some).collect();
+    for (idx, action) in view.log.iter().enumerate().rev() {
+        match action.kind {
+            ActionKind::Hint {
+                target,
+                ref touched,
+                ..
+            } if target == view.public_id => {
+                for &slot in touched {
+                    if let Some(&Some(now)) = slots.get(slot) {
+                        clued[now] = true;
+                    }
+                }
+            }
+            ActionKind::Play { slot, .. } | ActionKind::Discard { slot, .. }
+                if action.player == view.public_id =>
+            {
+                if idx < draws_until {
+                    slots.pop();
+                }
+                slots.insert(slot.min(slots.len()), None);
+            }
+            _ => {}
+        }
+    }
+
+    clued
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{play_out, GameRecord};
+    use crate::card::{Card, Color, Number};
+    use crate::game::{Game, GameBuilder};
+    use crate::moves::ActionKind;
+    use crate::player::UID;
+    use crate::rules::{generate_normal_deck, GameConfig, GameOverReason, Variant};
+    use std::collections::VecDeque;
+
+    fn random_game(seed: u64) -> GameRecord {
+        let config = GameConfig::standard(3).unwrap();
+        let game = Game::from_seed(config, generate_normal_deck(), seed).unwrap();
+        let mut agents: Vec<Box<RandomBot>> =
+            (0..3).map(|i| Box::new(RandomBot::new(seed + i))).collect();
+
+        play_out(game, &mut agents).unwrap()
+    }
+
+    #[test]
+    fn test_random_bot() {
+        for seed in 0..20 {
+            let record = random_game(seed);
+            assert!(record.score <= 25);
+            assert_eq!(record, random_game(seed));
+        }
+    }
+
+    #[test]
+    fn test_safe_bot() {
+        for (num_players, variant) in (2..=5).flat_map(|n| {
+            vec![
+                (n, Variant::Standard),
+                (n, Variant::Rainbow),
+                (n, Variant::Black),
+            ]
+        }) {
+            for seed in 0..10 {
+                let mut config = GameConfig::standard(num_players).unwrap();
+                config.variant = variant;
+                let deck = config.generate_deck();
+                let game = Game::from_seed(config, deck, seed).unwrap();
+                let mut agents: Vec<Box<SafeBot>> =
+                    (0..num_players).map(|_| Box::new(SafeBot::new())).collect();
+
+                let record = play_out(game, &mut agents).unwrap();
+                let misplays = record
+                    .log
+                    .iter()
+                    .filter(|a| matches!(a.kind, ActionKind::Play { success: false, .. }));
+                assert_eq!(misplays.count(), 0);
+                assert_ne!(record.reason, GameOverReason::StruckOut);
+                assert!(record.score > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_safe_bot_choices() {
+        let r1 = Card::new(Color::Red, Number::One);
+        let b1 = Card::new(Color::Blue, Number::One);
+        let g4 = Card::new(Color::Green, Number::Four);
+        let y3 = Card::new(Color::Yellow, Number::Three);
+
+        let mut game = GameBuilder::new(GameConfig::standard(2).unwrap(), VecDeque::new())
+            .hands(vec![vec![g4, r1], vec![y3, b1]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = (0..2).map(|p| game.uid_of(p).unwrap()).collect();
+
+        // Nothing is known yet, so tell the other player about their playable blue 1
+        let mut bot = SafeBot::new();
+        let mv = bot.choose_move(&game.view_for(uids[0]).unwrap());
+        assert_eq!(mv, HanabiMove::Hint((1, Hint::ColorHint(Color::Blue))));
+
+        // Once they know their red 1 is a 1, it's safe to play on an empty board
+        game.play_move(mv, uids[0]).unwrap();
+        game.play_move(
+            HanabiMove::Hint((0, Hint::NumberHint(Number::One))),
+            uids[1],
+        )
+        .unwrap();
+        let mv = bot.choose_move(&game.view_for(uids[0]).unwrap());
+        assert_eq!(mv, HanabiMove::Play(1));
+
+        // No hints left: discard the oldest card nobody has hinted
+        let mut view = game.view_for(uids[0]).unwrap();
+        view.hints = 0;
+        assert_eq!(SafeBot::oldest_discard(&view), Some(HanabiMove::Discard(0)));
+
+        // Rainbow: clued cards are kept even when the clue left more than one possible suit
+        let m3 = Card::new(Color::Rainbow, Number::Three);
+        let g4 = Card::new(Color::Green, Number::Four);
+        let w5 = Card::new(Color::White, Number::Five);
+        let y3 = Card::new(Color::Yellow, Number::Three);
+        let b1 = Card::new(Color::Blue, Number::One);
+
+        let mut config = GameConfig::standard(2).unwrap();
+        config.variant = Variant::Rainbow;
+        let deck: VecDeque<Card> = vec![Card::new(Color::Red, Number::Five)].into();
+        let mut game = GameBuilder::new(config, deck)
+            .hands(vec![vec![m3, g4, w5], vec![y3, b1]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = (0..2).map(|p| game.uid_of(p).unwrap()).collect();
+
+        // A red clue touches the rainbow 3, which could still be red or rainbow
+        game.play_move(HanabiMove::Hint((1, Hint::ColorHint(Color::Blue))), uids[0])
+            .unwrap();
+        game.play_move(HanabiMove::Hint((0, Hint::ColorHint(Color::Red))), uids[1])
+            .unwrap();
+        let view = game.view_for(uids[0]).unwrap();
+        assert_eq!(
+            view.my_knowledge[0].possible_colors(),
+            vec![Color::Red, Color::Rainbow]
+        );
+        assert_eq!(clued_slots(&view), vec![true, false, false]);
+        assert_eq!(SafeBot::oldest_discard(&view), Some(HanabiMove::Discard(1)));
+
+        // Clues that miss it narrow the white 5 down to white, but it still hasn't been touched
+        let moves = [
+            (0, HanabiMove::Hint((1, Hint::ColorHint(Color::Yellow)))),
+            (1, HanabiMove::Hint((0, Hint::ColorHint(Color::Green)))),
+            (0, HanabiMove::Hint((1, Hint::NumberHint(Number::One)))),
+            (1, HanabiMove::Hint((0, Hint::ColorHint(Color::Blue)))),
+            (0, HanabiMove::Hint((1, Hint::NumberHint(Number::Three)))),
+            (1, HanabiMove::Hint((0, Hint::ColorHint(Color::Yellow)))),
+        ];
+        for &(player, mv) in &moves {
+            game.play_move(mv, uids[player]).unwrap();
+        }
+
+        let view = game.view_for(uids[0]).unwrap();
+        assert_eq!(view.my_knowledge[2].possible_colors(), vec![Color::White]);
+        assert_eq!(clued_slots(&view), vec![true, true, false]);
+        assert_eq!(SafeBot::oldest_discard(&view), Some(HanabiMove::Discard(2)));
+
+        // Once the clued cards are gone, slots are tracked through the shifts and draws
+        game.play_move(HanabiMove::Discard(0), uids[0]).unwrap();
+        let mv = HanabiMove::Hint((0, Hint::NumberHint(Number::Five)));
+        game.play_move(mv, uids[1]).unwrap();
+        let view = game.view_for(uids[0]).unwrap();
+        assert_eq!(clued_slots(&view), vec![true, true, true]);
+    }
+}
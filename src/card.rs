@@ -1,22 +1,33 @@
 extern crate bitflags;
 
 use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
 
 use crate::errors::HanabiError;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 // TODO: make a macro that makes both Color, ColorKnowledge, and impls the From trait
 // TODO: same with Number, etc...
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Color {
     Red = 0b00001,
     White = 0b00010,
     Blue = 0b00100,
     Green = 0b01000,
     Yellow = 0b10000,
+    // Only in the rainbow variant, where every color clue touches it
+    Rainbow = 0b100000,
+    // Only in the black variant, which has a single copy of each card
+    Black = 0b1000000,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Number {
     One = 0b00001,
     Two = 0b00010,
@@ -25,16 +36,127 @@ pub enum Number {
     Five = 0b10000,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/**
+ * @brief The five suits of the standard game, which are also the colors that can be clued
+ */
+pub const COLORS: [Color; 5] = [
+    Color::Red,
+    Color::White,
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+];
+
+/**
+ * @brief Every suit that exists in any variant. Use Variant::suits() for the ones in play.
+ */
+pub const ALL_COLORS: [Color; 7] = [
+    Color::Red,
+    Color::White,
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+    Color::Rainbow,
+    Color::Black,
+];
+
+pub const NUMBERS: [Number; 5] = [
+    Number::One,
+    Number::Two,
+    Number::Three,
+    Number::Four,
+    Number::Five,
+];
+
+impl Color {
+    /**
+     * @brief Single letter used for the color in card notation, e.g. the 'r' in "r1"
+     */
+    pub fn letter(self) -> char {
+        match self {
+            Color::Red => 'r',
+            Color::White => 'w',
+            Color::Blue => 'b',
+            Color::Green => 'g',
+            Color::Yellow => 'y',
+            Color::Rainbow => 'm',
+            Color::Black => 'k',
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Color::Red => "red",
+            Color::White => "white",
+            Color::Blue => "blue",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Rainbow => "rainbow",
+            Color::Black => "black",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Accepts either the full name ("red") or the single letter ("r"), in any case
+impl FromStr for Color {
+    type Err = HanabiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "r" | "red" => Ok(Color::Red),
+            "w" | "white" => Ok(Color::White),
+            "b" | "blue" => Ok(Color::Blue),
+            "g" | "green" => Ok(Color::Green),
+            "y" | "yellow" => Ok(Color::Yellow),
+            "m" | "rainbow" => Ok(Color::Rainbow),
+            "k" | "black" => Ok(Color::Black),
+            _ => Err(HanabiError::InvalidColor(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digit = match self {
+            Number::One => 1,
+            Number::Two => 2,
+            Number::Three => 3,
+            Number::Four => 4,
+            Number::Five => 5,
+        };
+        write!(f, "{}", digit)
+    }
+}
+
+impl FromStr for Number {
+    type Err = HanabiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Number::One),
+            "2" => Ok(Number::Two),
+            "3" => Ok(Number::Three),
+            "4" => Ok(Number::Four),
+            "5" => Ok(Number::Five),
+            _ => Err(HanabiError::InvalidNumber(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Card {
     color: Color,
     number: Number,
 }
 
 impl Card {
-    // TODO: this is pub(crate) so that we can write a unit test in player.rs
-    // There might be a better way to structure this
-    pub(crate) fn new(color: Color, number: Number) -> Card {
+    // Cards can be made by anyone so that scenarios can be set up by hand; game::GameBuilder
+    // validates every card it is given against the deck composition
+    pub fn new(color: Color, number: Number) -> Card {
         Card {
             color,
             number,
@@ -56,6 +178,35 @@ impl Card {
     }
 }
 
+// Short notation: color letter followed by the number, e.g. "r1" or "b5"
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.color.letter(), self.number)
+    }
+}
+
+impl FromStr for Card {
+    type Err = HanabiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || HanabiError::InvalidCard(s.to_string());
+
+        let mut chars = s.chars();
+        let color = chars.next().ok_or_else(invalid)?;
+        let number = chars.as_str();
+        if !color.is_ascii_alphabetic() {
+            return Err(invalid());
+        }
+
+        Ok(Card::new(
+            color.to_string().parse().map_err(|_| invalid())?,
+            number.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CardView {
     inner: Card,
 }
@@ -70,6 +221,12 @@ impl CardView {
     }
 }
 
+impl fmt::Display for CardView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
 // Private here
 bitflags! {
     #[derive(Default)]
@@ -79,7 +236,9 @@ bitflags! {
         const BLUE   = Color::Blue   as u32;
         const GREEN  = Color::Green  as u32;
         const YELLOW = Color::Yellow as u32;
-        const ALL_COLORS = Self::RED.bits | Self::WHITE.bits | Self::BLUE.bits | Self::GREEN.bits | Self::YELLOW.bits;
+        const RAINBOW = Color::Rainbow as u32;
+        const BLACK  = Color::Black  as u32;
+        const ALL_COLORS = Self::RED.bits | Self::WHITE.bits | Self::BLUE.bits | Self::GREEN.bits | Self::YELLOW.bits | Self::RAINBOW.bits | Self::BLACK.bits;
     }
 }
 
@@ -95,6 +254,39 @@ bitflags! {
     }
 }
 
+// bitflags doesn't know about serde, so the knowledge sets are (de)serialized as their raw bits
+#[cfg(feature = "serde")]
+impl Serialize for ColorKnowledge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ColorKnowledge {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u32::deserialize(deserializer)?;
+        ColorKnowledge::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid color bits {:#b}", bits)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for NumberKnowledge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for NumberKnowledge {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u32::deserialize(deserializer)?;
+        NumberKnowledge::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid number bits {:#b}", bits)))
+    }
+}
+
 impl From<Color> for ColorKnowledge {
     fn from(color: Color) -> Self {
         match color {
@@ -103,6 +295,8 @@ impl From<Color> for ColorKnowledge {
             Color::Blue => ColorKnowledge::BLUE,
             Color::Green => ColorKnowledge::GREEN,
             Color::Yellow => ColorKnowledge::YELLOW,
+            Color::Rainbow => ColorKnowledge::RAINBOW,
+            Color::Black => ColorKnowledge::BLACK,
         }
     }
 }
@@ -121,19 +315,64 @@ impl From<Number> for NumberKnowledge {
 
 // The best way to keep knowledge about a card is to keep track of what you *don't* know about the
 // card. Much easier to keep track of.
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CardKnowledge {
     pub not_these_colors: ColorKnowledge,
     pub not_these_numbers: NumberKnowledge,
 }
 
+impl Default for CardKnowledge {
+    fn default() -> Self {
+        CardKnowledge::new()
+    }
+}
+
 impl CardKnowledge {
+    /**
+     * @brief Knowledge of a fresh card in the standard game: any of the five standard suits. Use
+     * for_suits() for variants with other suits.
+     */
     pub fn new() -> Self {
+        CardKnowledge::for_suits(&COLORS)
+    }
+
+    /**
+     * @brief What a player knows about a freshly drawn card: it belongs to one of the suits in
+     * play, nothing more
+     */
+    pub fn for_suits(suits: &[Color]) -> Self {
+        let in_play = suits
+            .iter()
+            .fold(ColorKnowledge::empty(), |acc, &color| acc | color.into());
+
         CardKnowledge {
-            not_these_colors: Default::default(),
+            not_these_colors: ColorKnowledge::ALL_COLORS ^ in_play,
             not_these_numbers: Default::default(),
         }
     }
+
+    /**
+     * @brief The colors this card could still be
+     */
+    pub fn possible_colors(&self) -> Vec<Color> {
+        ALL_COLORS
+            .iter()
+            .copied()
+            .filter(|&color| !self.not_these_colors.contains(color.into()))
+            .collect()
+    }
+
+    /**
+     * @brief The numbers this card could still be
+     */
+    pub fn possible_numbers(&self) -> Vec<Number> {
+        NUMBERS
+            .iter()
+            .copied()
+            .filter(|&number| !self.not_these_numbers.contains(number.into()))
+            .collect()
+    }
 }
 
 pub fn this_color(ck: CardKnowledge, color: Color) -> Result<CardKnowledge, HanabiError> {
@@ -142,7 +381,7 @@ pub fn this_color(ck: CardKnowledge, color: Color) -> Result<CardKnowledge, Hana
     // never indicate a card both "is" and "is not" a given color at the same time.
 
     if ck.not_these_colors == (ck.not_these_colors | color.into()) {
-        return Err(HanabiError::LogicError("Card was previously designated as not being this color, indicating an internal game logic error".to_string()));
+        return Err(HanabiError::ContradictsColor(color));
     }
 
     let new_colors = ColorKnowledge::ALL_COLORS ^ color.into();
@@ -153,10 +392,50 @@ pub fn this_color(ck: CardKnowledge, color: Color) -> Result<CardKnowledge, Hana
     })
 }
 
+/**
+ * @brief A color clue touched the card, so it is one of the given colors: the clued color itself,
+ * plus any suit (like rainbow) that every clue of that color touches
+ */
+pub fn one_of_colors(
+    ck: CardKnowledge,
+    clue: Color,
+    colors: ColorKnowledge,
+) -> Result<CardKnowledge, HanabiError> {
+    // Sanity check -- see above in this_color()
+    let new_colors = ck.not_these_colors | (ColorKnowledge::ALL_COLORS ^ colors);
+    if new_colors == ColorKnowledge::ALL_COLORS {
+        return Err(HanabiError::ContradictsColor(clue));
+    }
+
+    Ok(CardKnowledge {
+        not_these_colors: new_colors,
+        not_these_numbers: ck.not_these_numbers,
+    })
+}
+
+/**
+ * @brief A color clue didn't touch the card, so it is none of the colors that clue touches
+ */
+pub fn none_of_colors(
+    ck: CardKnowledge,
+    colors: ColorKnowledge,
+) -> Result<CardKnowledge, HanabiError> {
+    let new_colors = ck.not_these_colors | colors;
+
+    if new_colors == ColorKnowledge::ALL_COLORS {
+        Err(HanabiError::NoColorLeft)
+    } else {
+        Ok(CardKnowledge {
+            not_these_colors: new_colors,
+            not_these_numbers: ck.not_these_numbers,
+        })
+    }
+}
+
 pub fn this_number(ck: CardKnowledge, number: Number) -> Result<CardKnowledge, HanabiError> {
     // Sanity check -- see above in this_color()
     if ck.not_these_numbers == (ck.not_these_numbers | number.into()) {
-        return Err(HanabiError::LogicError("Card was previously designated as not being this number, indicating an internal game logic error".to_string()));
+        return Err(HanabiError::ContradictsNumber(number));
     }
 
     let new_numbers = NumberKnowledge::ALL_NUMBERS ^ number.into();
@@ -171,9 +450,7 @@ pub fn not_this_color(ck: CardKnowledge, color: Color) -> Result<CardKnowledge,
     let new_colors = ck.not_these_colors | color.into();
 
     if new_colors == ColorKnowledge::ALL_COLORS {
-        Err(HanabiError::LogicError(
-            "Impossible for a card to not be every color".to_string(),
-        ))
+        Err(HanabiError::NoColorLeft)
     } else {
         Ok(CardKnowledge {
             not_these_colors: new_colors,
@@ -186,9 +463,7 @@ pub fn not_this_number(ck: CardKnowledge, number: Number) -> Result<CardKnowledg
     let new_numbers = ck.not_these_numbers | number.into();
 
     if new_numbers == NumberKnowledge::ALL_NUMBERS {
-        Err(HanabiError::LogicError(
-            "Impossible for a card to not be every number".to_string(),
-        ))
+        Err(HanabiError::NoNumberLeft)
     } else {
         Ok(CardKnowledge {
             not_these_colors: ck.not_these_colors,
@@ -197,29 +472,17 @@ pub fn not_this_number(ck: CardKnowledge, number: Number) -> Result<CardKnowledg
     }
 }
 
-// This is messy... It doesn't really need to take a functor for card frequencies... this code used
-// to live in rules.rs but it needs to construct `Card`s and I wanted to make Card have a "private
-// constructor" so I moved here but kept card_frequencies in rules.rs
-pub fn generate_deck<F: Fn(&Number) -> u8>(card_frequencies: F) -> VecDeque<Card> {
-    let colors = vec![
-        Color::Red,
-        Color::White,
-        Color::Blue,
-        Color::Green,
-        Color::Yellow,
-    ];
-    let numbers = vec![
-        Number::One,
-        Number::Two,
-        Number::Three,
-        Number::Four,
-        Number::Five,
-    ];
-
+// This code used to live in rules.rs but it needs to construct `Card`s and I wanted to make Card
+// have a "private constructor" so I moved here but kept card_frequencies in rules.rs. The functor
+// takes the suit as well since some suits (like black) have their own frequencies.
+pub fn generate_deck<F: Fn(&Color, &Number) -> u8>(
+    suits: &[Color],
+    card_frequencies: F,
+) -> VecDeque<Card> {
     let mut deck = VecDeque::new();
-    for col in &colors {
-        for num in &numbers {
-            for _ in 0..card_frequencies(num) {
+    for col in suits {
+        for num in &NUMBERS {
+            for _ in 0..card_frequencies(col, num) {
                 deck.push_front(Card::new(*col, *num));
             }
         }
@@ -244,6 +507,8 @@ mod tests {
                     | ColorKnowledge::BLUE
                     | ColorKnowledge::YELLOW
                     | ColorKnowledge::GREEN
+                    | ColorKnowledge::RAINBOW
+                    | ColorKnowledge::BLACK
             );
         }
         {
@@ -258,15 +523,22 @@ mod tests {
     fn test_not_this_color() {
         let mut card_knowledge = CardKnowledge::new();
 
+        // A standard card already can't be any of the variant suits
+        let variant_suits = ColorKnowledge::RAINBOW | ColorKnowledge::BLACK;
+        assert_eq!(card_knowledge.not_these_colors, variant_suits);
+
         // First color is fine.
         card_knowledge = not_this_color(card_knowledge, Color::Green).unwrap();
-        assert_eq!(card_knowledge.not_these_colors, ColorKnowledge::GREEN);
+        assert_eq!(
+            card_knowledge.not_these_colors,
+            variant_suits | ColorKnowledge::GREEN
+        );
 
         // Second color is fine
         card_knowledge = not_this_color(card_knowledge, Color::Red).unwrap();
         assert_eq!(
             card_knowledge.not_these_colors,
-            ColorKnowledge::GREEN | ColorKnowledge::RED
+            variant_suits | ColorKnowledge::GREEN | ColorKnowledge::RED
         );
 
         // Third and Fourth color is fine
@@ -274,7 +546,8 @@ mod tests {
         card_knowledge = not_this_color(card_knowledge, Color::White).unwrap();
         assert_eq!(
             card_knowledge.not_these_colors,
-            ColorKnowledge::GREEN
+            variant_suits
+                | ColorKnowledge::GREEN
                 | ColorKnowledge::RED
                 | ColorKnowledge::BLUE
                 | ColorKnowledge::WHITE
@@ -289,6 +562,75 @@ mod tests {
         assert!(err.is_err());
     }
 
+    #[test]
+    fn test_card_notation() {
+        let deck = generate_deck(&ALL_COLORS, |_, _| 1);
+        for card in &deck {
+            let text = card.to_string();
+            assert_eq!(text.len(), 2);
+            assert_eq!(&text.parse::<Card>().unwrap(), card);
+            assert_eq!(card.view().to_string(), text);
+        }
+
+        assert_eq!(
+            Card::new(Color::Red, Number::One).to_string(),
+            "r1".to_string()
+        );
+        assert_eq!(
+            "B5".parse::<Card>().unwrap(),
+            Card::new(Color::Blue, Number::Five)
+        );
+
+        for bad in &["", "r", "1r", "x1", "r6", "r12", "red1"] {
+            assert!(matches!(
+                bad.parse::<Card>(),
+                Err(HanabiError::InvalidCard(ref s)) if s == bad
+            ));
+        }
+    }
+
+    #[test]
+    fn test_color_and_number_notation() {
+        for color in &ALL_COLORS {
+            assert_eq!(color.to_string().parse::<Color>().unwrap(), *color);
+            assert_eq!(color.letter().to_string().parse::<Color>().unwrap(), *color);
+        }
+        assert_eq!("Green".parse::<Color>().unwrap(), Color::Green);
+        assert!(matches!(
+            "purple".parse::<Color>(),
+            Err(HanabiError::InvalidColor(_))
+        ));
+
+        assert_eq!("3".parse::<Number>().unwrap(), Number::Three);
+        assert_eq!(Number::Four.to_string(), "4");
+        assert!(matches!(
+            "0".parse::<Number>(),
+            Err(HanabiError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_possible_colors_and_numbers() {
+        assert_eq!(CardKnowledge::new().possible_colors(), COLORS.to_vec());
+        assert_eq!(CardKnowledge::default(), CardKnowledge::new());
+        assert_eq!(
+            CardKnowledge::for_suits(&ALL_COLORS).possible_colors(),
+            ALL_COLORS.to_vec()
+        );
+
+        let mut card_knowledge = CardKnowledge::for_suits(&COLORS);
+        assert_eq!(card_knowledge.possible_colors(), COLORS.to_vec());
+        assert_eq!(card_knowledge.possible_numbers(), NUMBERS.to_vec());
+
+        card_knowledge = not_this_color(card_knowledge, Color::White).unwrap();
+        card_knowledge = this_number(card_knowledge, Number::Three).unwrap();
+        assert_eq!(
+            card_knowledge.possible_colors(),
+            vec![Color::Red, Color::Blue, Color::Green, Color::Yellow]
+        );
+        assert_eq!(card_knowledge.possible_numbers(), vec![Number::Three]);
+    }
+
     #[test]
     fn test_this_number() {
         {
@@ -310,6 +652,38 @@ mod tests {
             let err = this_number(card_knowledge, Number::Four);
 
             assert!(err.is_err());
+            assert!(err.unwrap_err().to_string().contains("not being 4,"));
         }
     }
+
+    #[test]
+    fn test_rainbow_clues() {
+        let red_clue = ColorKnowledge::RED | ColorKnowledge::RAINBOW;
+        let blue_clue = ColorKnowledge::BLUE | ColorKnowledge::RAINBOW;
+
+        // Touched by red: red or rainbow. Then touched by blue as well: must be rainbow
+        let mut card_knowledge = CardKnowledge::for_suits(&ALL_COLORS[..6]);
+        card_knowledge = one_of_colors(card_knowledge, Color::Red, red_clue).unwrap();
+        assert_eq!(
+            card_knowledge.possible_colors(),
+            vec![Color::Red, Color::Rainbow]
+        );
+        card_knowledge = one_of_colors(card_knowledge, Color::Blue, blue_clue).unwrap();
+        assert_eq!(card_knowledge.possible_colors(), vec![Color::Rainbow]);
+
+        // Missed by red: neither red nor rainbow, so another red clue can't touch it
+        let mut card_knowledge = CardKnowledge::for_suits(&ALL_COLORS[..6]);
+        card_knowledge = none_of_colors(card_knowledge, red_clue).unwrap();
+        assert_eq!(
+            card_knowledge.possible_colors(),
+            vec![Color::White, Color::Blue, Color::Green, Color::Yellow]
+        );
+        assert!(matches!(
+            one_of_colors(card_knowledge, Color::Red, red_clue),
+            Err(HanabiError::ContradictsColor(Color::Red))
+        ));
+        assert!(HanabiError::ContradictsColor(Color::Red)
+            .to_string()
+            .contains("not being red,"));
+    }
 }
@@ -1,6 +1,10 @@
 use thiserror::Error;
 
-#[derive(Debug)]
+use crate::card::{Card, Color, Number};
+use crate::moves::Hint;
+use crate::player::{PubID, UID};
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct Limits {
     pub lo: usize,
     pub hi: usize,
@@ -10,14 +14,99 @@ pub struct Limits {
 pub enum HanabiError {
     #[error("Int conversion failed")]
     IndexError(#[from] std::num::TryFromIntError),
-    #[error("Invalid move: {0}")]
-    InvalidMove(String),
-    #[error("LogicError: {0}")]
-    LogicError(String),
     #[error("Game is finished")]
     GameFinished,
     #[error("invalid index {idx}, expected at least {} and at most {}", .limits.lo, .limits.hi)]
     OutOfBounds { idx: usize, limits: Limits },
     #[error("unknown error")]
     Unknown,
+
+    // Players
+    #[error("No player has UID {0}")]
+    UnknownPlayer(UID),
+    #[error("No player has public ID {0}")]
+    UnknownPublicId(PubID),
+    #[error("Player {0} can't look at their own cards")]
+    OwnCards(PubID),
+
+    // Moves
+    #[error("It's player {active}'s turn, not player {requested}'s")]
+    NotYourTurn { requested: PubID, active: PubID },
+    #[error("There are no hints left to give")]
+    NoHintsLeft,
+    #[error("Can't discard while all {max_hints} hints are available")]
+    CannotDiscardAtMaxHints { max_hints: u8 },
+    #[error("A {hint} hint doesn't touch any of player {target}'s cards")]
+    HintTouchesNoCards { target: PubID, hint: Hint },
+    #[error("{0} can't be named in a clue in this variant")]
+    NotAClueColor(Color),
+    #[error("Player {0} can't give a hint to themselves")]
+    SelfHint(PubID),
+    #[error("Slot {idx} is out of range of a hand of {hand_len} cards")]
+    IndexOutOfRange { idx: usize, hand_len: usize },
+
+    // Card knowledge
+    #[error("Card was previously designated as not being {0}, indicating an internal game logic error")]
+    ContradictsColor(Color),
+    #[error("Card was previously designated as not being {0}, indicating an internal game logic error")]
+    ContradictsNumber(Number),
+    #[error("Impossible for a card to not be every color")]
+    NoColorLeft,
+    #[error("Impossible for a card to not be every number")]
+    NoNumberLeft,
+
+    // Setting up a game
+    #[error("Invalid number of players {num_players}, expected at least {} and at most {}", .limits.lo, .limits.hi)]
+    InvalidPlayerCount { num_players: usize, limits: Limits },
+    #[error("Players must be dealt at least one card")]
+    EmptyHands,
+    #[error("The strike limit must be at least one")]
+    NoStrikesAllowed,
+    #[error("A deck of {deck_len} cards can't deal {needed} cards to the players")]
+    DeckTooSmall { deck_len: usize, needed: usize },
+    #[error("Can't start with {hints} hints, the maximum is {max_hints}")]
+    TooManyHints { hints: u8, max_hints: u8 },
+    #[error("Can't start with {strikes} strikes, the game ends at {max_strikes}")]
+    TooManyStrikes { strikes: u8, max_strikes: u8 },
+    #[error("Got {agents} agents for {num_players} players")]
+    WrongNumberOfAgents { agents: usize, num_players: usize },
+    #[error("Got {hands} hands for {num_players} players")]
+    WrongNumberOfHands { hands: usize, num_players: usize },
+    #[error("Hands can hold at most {hand_size} cards, got {len}")]
+    HandTooBig { len: usize, hand_size: usize },
+    #[error("{0} can't be on the board")]
+    NotOnBoard(Card),
+    #[error("Too many copies of {0} for this deck")]
+    TooManyCopies(Card),
+
+    // Replays
+    #[error("Move {turn} of the replay is illegal: {source}")]
+    IllegalReplayMove {
+        turn: usize,
+        source: Box<HanabiError>,
+    },
+
+    // hanab.live import and export
+    #[error("The hanab.live variant '{0}' isn't supported")]
+    HanabLiveVariant(String),
+    #[error("There is no hanab.live card with suit index {suit_index} and rank {rank}")]
+    HanabLiveCard { suit_index: usize, rank: usize },
+    #[error("hanab.live action {0} doesn't fit the game")]
+    HanabLiveAction(usize),
+    #[error("hanab.live games where player {0} moves first aren't supported")]
+    HanabLiveStartingPlayer(usize),
+    #[error("Only games that started from a normal deal can be exported to hanab.live")]
+    HanabLiveNotFromStart,
+
+    // Parsing the text notation
+    #[error("'{0}' is not a color")]
+    InvalidColor(String),
+    #[error("'{0}' is not a number")]
+    InvalidNumber(String),
+    #[error("'{0}' is not a card, expected something like 'r1'")]
+    InvalidCard(String),
+    #[error("'{0}' is not a hint, expected a color or a number")]
+    InvalidHint(String),
+    #[error("'{0}' is not a move, expected 'play <slot>', 'discard <slot>' or 'clue <player> <color|number>'")]
+    InvalidMoveNotation(String),
 }
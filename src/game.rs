@@ -1,29 +1,45 @@
 use std::collections::VecDeque;
 use std::convert::TryInto;
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::card::{Card, CardKnowledge, CardView};
-use crate::errors::HanabiError;
-use crate::moves::{HanabiMove, Hint, HintForPlayer};
-use crate::player::{generate_players, get_public_id, Player, PubID, UID};
-use crate::rules::{number_below, GameResultState, MAX_HINTS, NUM_BOMBS};
-use crate::rules::{MAX_PLAYERS, MIN_PLAYERS};
+use crate::errors::{HanabiError, Limits};
+use crate::moves::{Action, ActionKind, HanabiMove, Hint, HintForPlayer};
+use crate::player::{generate_players, get_id, get_public_id, Player, PubID, UID};
+use crate::rules::{game_result, number_below, playable_on, GameConfig, GameResultState};
+use crate::view::{OtherHand, PlayerView, SpectatorHand, SpectatorView};
 
 /**
  * @brief Shuffle an existing deck
  */
-fn shuffle_deck(deck: VecDeque<Card>) -> VecDeque<Card> {
+fn shuffle_deck<R: Rng + ?Sized>(deck: VecDeque<Card>, rng: &mut R) -> VecDeque<Card> {
     // TODO: copying out and back in sucks but it'll work for now
     let mut temp = Vec::from(deck);
-    temp.shuffle(&mut thread_rng());
+    temp.shuffle(rng);
 
     VecDeque::from(temp)
 }
 
+/**
+ * @brief What a move overwrote that its Action doesn't record
+ */
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Undo {
+    turns_since_last_pickup: Option<usize>,
+    // The hinted player's whole hand before a hint, or the played/discarded card's knowledge
+    knowledge: Vec<CardKnowledge>,
+}
+
 /* Game */
 //#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Game {
     // Stacks of cards
     deck: VecDeque<Card>,
@@ -36,67 +52,202 @@ pub struct Game {
 
     // Counters
     pub hints: u8,
-    pub bombs: u8,
+    pub strikes: u8,
     //pub turn_number: usize,
     // TODO: consider removing this since it can likely be calculated from the number of cards in
     // the players' hands.
     pub turns_since_last_pickup: Option<usize>,
+
+    // Everything that has happened so far, oldest first
+    log: Vec<Action>,
+    // One entry per action in the log, holding whatever else is needed to take it back
+    undo_stack: Vec<Undo>,
+
+    config: GameConfig,
 }
 
 impl Game {
     /**
-     * @brief Create a new Game
+     * @brief Create a new Game from the given config, shuffling the deck
      */
-    pub fn new(num_players: usize, mut deck: VecDeque<Card>) -> Result<Self, HanabiError> {
-        if num_players > MAX_PLAYERS.into() || num_players < MIN_PLAYERS.into() {
-            return Err(HanabiError::LogicError(
-                "Invalid number of players".to_string(),
-            ));
+    pub fn new(config: GameConfig, deck: VecDeque<Card>) -> Result<Self, HanabiError> {
+        Game::with_rng(config, deck, &mut thread_rng())
+    }
+
+    /**
+     * @brief Create a new Game whose deal is fully determined by the given seed. The same seed
+     * always produces the same deck order and the same player UIDs.
+     */
+    pub fn from_seed(
+        config: GameConfig,
+        deck: VecDeque<Card>,
+        seed: u64,
+    ) -> Result<Self, HanabiError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Game::with_rng(config, deck, &mut rng)
+    }
+
+    /**
+     * @brief Create a new Game, drawing the shuffle and the player UIDs from the given RNG
+     */
+    pub fn with_rng<R: Rng + ?Sized>(
+        config: GameConfig,
+        mut deck: VecDeque<Card>,
+        rng: &mut R,
+    ) -> Result<Self, HanabiError> {
+        config.validate()?;
+
+        if deck.len() < config.hand_size * config.num_players {
+            return Err(HanabiError::DeckTooSmall {
+                deck_len: deck.len(),
+                needed: config.hand_size * config.num_players,
+            });
         }
 
-        // TODO: consider moving generate_deck code here
-        deck = shuffle_deck(deck);
+        deck = shuffle_deck(deck, rng);
         let mut game = Game {
             deck,
             discard: Vec::new(),
             board: Vec::new(),
-            players: generate_players(num_players),
+            players: generate_players(config.num_players, rng),
             active_player: 0,
-            hints: MAX_HINTS,
-            bombs: NUM_BOMBS,
+            hints: config.max_hints,
+            strikes: 0,
             //turn_number: 0,
             turns_since_last_pickup: None,
+            log: Vec::new(),
+            undo_stack: Vec::new(),
+            config,
         };
 
         game.deal_cards();
+        game.start_final_round_if_deck_empty();
 
         Ok(game)
     }
 
     /**
-     * @brief Deal cards from the Deck into Player's hands
-     *  According to the rules:
-     *      Deal a hand of 5 cards to each with 2 or 3 players
-     *      Deal a hand of 4 cards to each with 4 or 5 players
+     * @brief Deal cards from the Deck into Player's hands, config.hand_size cards each
      */
     fn deal_cards(&mut self) {
-        let cards_to_deal = match self.players.len() {
-            2 | 3 => 5,
-            4 | 5 => 4,
-            _ => unreachable!(),
-        };
-
-        for _ in 0..cards_to_deal {
+        let knowledge = self.fresh_knowledge();
+        for _ in 0..self.config.hand_size {
             for p in &mut self.players {
                 let c = self
                     .deck
                     .pop_front()
                     .expect("deck doesn't have enough cards");
-                p.push_card(c);
+                p.push_card_with_knowledge(c, knowledge.clone());
             }
         }
     }
 
+    /**
+     * @brief What everyone knows about a card nobody has been told anything about yet
+     */
+    fn fresh_knowledge(&self) -> CardKnowledge {
+        CardKnowledge::for_suits(self.config.variant.suits())
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /**
+     * @brief Every move made so far in this game, oldest first
+     */
+    pub fn log(&self) -> &[Action] {
+        &self.log
+    }
+
+    /**
+     * @brief Move the top card of the deck into a player's hand. Once the deck has run out this
+     * does nothing and the player simply continues with a smaller hand.
+     *
+     * @return The card that was drawn, if any
+     */
+    fn draw_card(&mut self, pub_id: PubID) -> Option<Card> {
+        let new_card = self.deck.pop_front()?;
+        let knowledge = self.fresh_knowledge();
+        self.players[pub_id as usize].push_card_with_knowledge(new_card, knowledge);
+
+        Some(new_card)
+    }
+
+    pub fn deck_len(&self) -> usize {
+        self.deck.len()
+    }
+
+    /**
+     * @brief Snapshot of everything the given player is allowed to know about the game
+     */
+    pub fn view_for(&self, requesting_player_uid: UID) -> Result<PlayerView, HanabiError> {
+        let me = get_public_id(&self.players, requesting_player_uid)?;
+
+        let others = self
+            .players
+            .iter()
+            .filter(|p| p.public_id != me)
+            .map(|p| OtherHand {
+                public_id: p.public_id,
+                cards: p.hand_view(),
+                knowledge: p.get_knowledge(),
+            })
+            .collect();
+
+        Ok(PlayerView {
+            public_id: me,
+            config: self.config.clone(),
+            my_knowledge: self.players[me as usize].get_knowledge(),
+            others,
+            board: self.board.clone(),
+            discard: self.discard.clone(),
+            deck_len: self.deck.len(),
+            hints: self.hints,
+            strikes: self.strikes,
+            active_player: self.active_player,
+            turns_since_last_pickup: self.turns_since_last_pickup,
+            log: self.log.iter().map(|action| action.seen_by(me)).collect(),
+        })
+    }
+
+    /**
+     * @brief Omniscient snapshot of the game for referees and replay tools. The remaining deck
+     * order is only included if `reveal_deck` is set.
+     *
+     * Never hand this to a player; use view_for() instead.
+     */
+    pub fn spectator_view(&self, reveal_deck: bool) -> SpectatorView {
+        let hands = self
+            .players
+            .iter()
+            .map(|p| SpectatorHand {
+                public_id: p.public_id,
+                cards: (0..p.hand_len())
+                    .map(|idx| p.hand_at(idx).clone())
+                    .collect(),
+            })
+            .collect();
+
+        SpectatorView {
+            config: self.config.clone(),
+            hands,
+            board: self.board.clone(),
+            discard: self.discard.clone(),
+            deck_len: self.deck.len(),
+            deck: if reveal_deck {
+                Some(self.deck.iter().copied().collect())
+            } else {
+                None
+            },
+            hints: self.hints,
+            strikes: self.strikes,
+            active_player: self.active_player,
+            turns_since_last_pickup: self.turns_since_last_pickup,
+            log: self.log.clone(),
+        }
+    }
+
     /**
      * @brief Get a look at another player's hand-knowledge.
      *
@@ -110,7 +261,7 @@ impl Game {
             .iter()
             .find(|p| target_player_pubid == p.public_id)
             .map(|p| p.get_knowledge())
-            .ok_or_else(|| HanabiError::InvalidMove("Unknown cohort player ID".to_string()))
+            .ok_or(HanabiError::UnknownPublicId(target_player_pubid))
     }
 
     /**
@@ -131,26 +282,16 @@ impl Game {
             .players
             .iter()
             .find(|p| p.uid == requesting_player_uid)
-            .ok_or_else(|| {
-                HanabiError::InvalidMove(
-                    "You must provide a valid UID in order to request player cards".to_string(),
-                )
-            })?;
+            .ok_or(HanabiError::UnknownPlayer(requesting_player_uid))?;
 
         let target_player = &self
             .players
             .iter()
             .find(|p| p.public_id == target_player_pubid)
-            .ok_or_else(|| {
-                HanabiError::InvalidMove(
-                    "The public ID given does not correspond to any player".to_string(),
-                )
-            })?;
+            .ok_or(HanabiError::UnknownPublicId(target_player_pubid))?;
 
         if target_player.public_id == requesting_player.public_id {
-            return Err(HanabiError::InvalidMove(
-                "You can't request to see your own cards".to_string(),
-            ));
+            return Err(HanabiError::OwnCards(requesting_player.public_id));
         }
 
         Ok(target_player.hand_view())
@@ -161,24 +302,67 @@ impl Game {
      *      1.) If there are no hints left, you can't hint
      *      2.) If all hints are available, then you can't discard
      *      3.) The index of the card to play or discard must be valid
+     *      4.) Any hint must be a legal hint (see legal_hint)
+     *
+     * @return Ok if the move is legal, otherwise the rule it breaks
      */
-    fn legal_move(&self, mv: &HanabiMove, pub_id: PubID) -> Result<bool, HanabiError> {
+    fn legal_move(&self, mv: &HanabiMove, pub_id: PubID) -> Result<(), HanabiError> {
         if pub_id >= self.players.len().try_into()? {
-            return Err(HanabiError::InvalidMove(
-                "PubID is out of range for num players".to_string(),
-            ));
+            return Err(self.pub_id_out_of_bounds(pub_id));
         }
 
-        Ok(match mv {
+        let hand_len = self.players[pub_id as usize].hand_len();
+        match mv {
             // You have hints to give and it's a legal hint
-            HanabiMove::Hint(hint) => self.hints != 0 && self.legal_hint(&hint)?,
+            HanabiMove::Hint(hint) => {
+                if self.hints == 0 {
+                    return Err(HanabiError::NoHintsLeft);
+                }
+                self.legal_hint(hint, pub_id)
+            }
             HanabiMove::Discard(idx) => {
-                self.hints != MAX_HINTS && idx < &self.players[pub_id as usize].hand_len()
-                //self.hints != MAX_HINTS && idx < &self.player_hands[pub_id as usize].len()
+                if self.hints == self.config.max_hints {
+                    return Err(HanabiError::CannotDiscardAtMaxHints {
+                        max_hints: self.config.max_hints,
+                    });
+                }
+                if *idx >= hand_len {
+                    return Err(HanabiError::IndexOutOfRange {
+                        idx: *idx,
+                        hand_len,
+                    });
+                }
+                Ok(())
             }
-            //HanabiMove::Play(idx) => idx < &self.player_hands[pub_id as usize].len(),
-            HanabiMove::Play(idx) => idx < &self.players[pub_id as usize].hand_len(),
-        })
+            HanabiMove::Play(idx) => {
+                if *idx >= hand_len {
+                    return Err(HanabiError::IndexOutOfRange {
+                        idx: *idx,
+                        hand_len,
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /**
+     * @brief Every move the given player could legally make right now, worked out from what they
+     * can see (see PlayerView::legal_moves). This is empty if it isn't their turn or the game is
+     * over.
+     */
+    pub fn legal_moves(&self, requesting_player_uid: UID) -> Result<Vec<HanabiMove>, HanabiError> {
+        Ok(self.view_for(requesting_player_uid)?.legal_moves())
+    }
+
+    fn pub_id_out_of_bounds(&self, pub_id: PubID) -> HanabiError {
+        HanabiError::OutOfBounds {
+            idx: pub_id.into(),
+            limits: Limits {
+                lo: 0,
+                hi: self.players.len() - 1,
+            },
+        }
     }
 
     /**
@@ -188,159 +372,480 @@ impl Game {
      *   of that number
      * 2.) You can't give a hint for a color to a player if the player doesn't have any cards
      *   of that color
+     * 3.) You can't give a hint to yourself
+     *
+     * @return Ok if the hint is legal, otherwise the rule it breaks
      */
-    fn legal_hint(&self, hint: &HintForPlayer) -> Result<bool, HanabiError> {
+    fn legal_hint(&self, hint: &HintForPlayer, giver: PubID) -> Result<(), HanabiError> {
         let (target_player_id, hint_type) = hint;
 
         if target_player_id >= &(self.players.len() as PubID) {
-            return Err(HanabiError::InvalidMove(
-                "PubID is out of range for num players".to_string(),
-            ));
+            return Err(self.pub_id_out_of_bounds(*target_player_id));
+        }
+
+        if *target_player_id == giver {
+            return Err(HanabiError::SelfHint(giver));
+        }
+
+        if let Hint::ColorHint(color) = hint_type {
+            if !self.config.variant.clue_colors().contains(color) {
+                return Err(HanabiError::NotAClueColor(*color));
+            }
         }
 
         let target_player = &self.players[*target_player_id as usize];
-        Ok(match hint_type {
-            Hint::ColorHint(color) => target_player.any_of_color(*color),
-            Hint::NumberHint(number) => target_player.any_of_number(*number),
-        })
+        if !target_player.any_touched_by(*hint_type, self.config.variant) {
+            return Err(HanabiError::HintTouchesNoCards {
+                target: *target_player_id,
+                hint: *hint_type,
+            });
+        }
+
+        Ok(())
     }
 
     /**
      * @brief Checks to see if playing the given card is a legal play according to the rules.
      */
     fn card_playable(&self, card: &Card) -> bool {
-        let move_color = &card.color();
-        let highest_current_number = self
-            .board
-            .iter()
-            .filter(|c| &c.color() == move_color)
-            .map(|c| c.number())
-            .max();
+        playable_on(&self.board, card.color(), card.number())
+    }
 
-        // One condition must be satisfied to be playable:
-        //  1.) The highest number of this suit must be the number just below this card (if any)
+    pub fn strikes_left(&self) -> u8 {
+        self.config.max_strikes.saturating_sub(self.strikes)
+    }
 
-        number_below(card.number()) == highest_current_number
+    pub fn finished(&self) -> GameResultState {
+        game_result(
+            &self.config,
+            &self.board,
+            self.strikes,
+            self.turns_since_last_pickup,
+        )
     }
 
-    fn score(&self) -> usize {
-        self.board.len()
+    pub fn is_players_turn(&self, player_id: PubID) -> bool {
+        player_id == self.active_player
     }
 
-    pub fn finished(&self) -> GameResultState {
-        // You win -- got 25
-        if 25 == self.board.len() {
-            return GameResultState::Finished(25);
-        }
-        // You lose --
-        if 0 == self.bombs {
-            return GameResultState::Finished(self.score());
-        }
-        // You ran out of turns
-        if Some(self.players.len()) == self.turns_since_last_pickup {
-            return GameResultState::Finished(self.score());
-        }
+    pub fn active_player(&self) -> PubID {
+        self.active_player
+    }
 
-        // TODO: additional condition
-        // there's no way to get any more points given the cards on the board and what is in the
-        // discard pile
-        // if self.no_way_to_win() {
-        //     return GameResultState::Lose;
-        // }
+    /**
+     * @brief Look up a player's private UID. This is meant for whoever hosts the game (a server,
+     * a hot-seat terminal, a game runner), never for the players themselves.
+     */
+    pub fn uid_of(&self, pub_id: PubID) -> Result<UID, HanabiError> {
+        get_id(&self.players, pub_id)
+    }
 
-        GameResultState::InProgress
+    /**
+     * @brief A game that starts with nothing left to draw is already in its final round, so
+     * every player gets exactly one turn
+     */
+    fn start_final_round_if_deck_empty(&mut self) {
+        if self.deck.is_empty() {
+            self.turns_since_last_pickup = Some(0);
+        }
     }
 
-    pub fn is_players_turn(&self, player_id: PubID) -> bool {
-        player_id == self.active_player
+    /**
+     * @brief Pass the turn on to the next player. Once the deck has run out, this also counts
+     * down the final round: every player (including the one who drew the last card) gets exactly
+     * one more turn.
+     */
+    fn end_turn(&mut self) {
+        match self.turns_since_last_pickup.as_mut() {
+            Some(turns) => *turns += 1,
+            None if self.deck.is_empty() => self.turns_since_last_pickup = Some(0),
+            None => {}
+        }
+
+        self.active_player = (self.active_player + 1) % self.players.len() as PubID;
     }
 
+    /**
+     * @brief Make a move for the given player and return what happened.
+     *
+     * The returned Action is unredacted: it shows the card the mover just drew. It is meant for
+     * the host; send each player action.seen_by(their public id), or a fresh view_for() instead.
+     */
     pub fn play_move(
         &mut self,
         play: HanabiMove,
         requesting_player_uid: UID,
-    ) -> Result<(), HanabiError> {
+    ) -> Result<Action, HanabiError> {
         // Check to make sure it is this player's turn
         let requester_pub_id = get_public_id(&self.players, requesting_player_uid)?;
 
-        if let GameResultState::Finished(_) = self.finished() {
+        if let GameResultState::Finished(..) = self.finished() {
             return Err(HanabiError::GameFinished);
         }
 
         if self.active_player != requester_pub_id {
-            return Err(HanabiError::InvalidMove("It's not your turn!".to_string()));
+            return Err(HanabiError::NotYourTurn {
+                requested: requester_pub_id,
+                active: self.active_player,
+            });
         }
 
         // Check to make sure this is a legal move
         self.legal_move(&play, requester_pub_id)?;
 
+        let knowledge = match play {
+            HanabiMove::Hint((pub_id, _)) => self.players[pub_id as usize].get_knowledge(),
+            HanabiMove::Discard(idx) | HanabiMove::Play(idx) => {
+                vec![self.players[requester_pub_id as usize]
+                    .hand_at(idx)
+                    .1
+                    .clone()]
+            }
+        };
+        let undo = Undo {
+            turns_since_last_pickup: self.turns_since_last_pickup,
+            knowledge,
+        };
+
         // If the card is playable, play it
         // Else, this is a bomb and move it to the discard
-        match play {
+        let kind = match play {
             HanabiMove::Hint((pub_id, hint)) => {
-                self.players[pub_id as usize].give_hint(hint)?;
+                // legal_move already checked for a token, but never let the count wrap around
+                let hints = self.hints.checked_sub(1).ok_or(HanabiError::NoHintsLeft)?;
+                let touched = self.players[pub_id as usize].give_hint(hint, self.config.variant)?;
+                self.hints = hints;
+
+                ActionKind::Hint {
+                    target: pub_id,
+                    hint,
+                    touched,
+                }
             }
             HanabiMove::Discard(idx) => {
                 // Remove
                 let p = &mut self.players[requester_pub_id as usize];
-                if idx >= p.hand_len() {
-                    return Err(HanabiError::InvalidMove(format!(
-                        "Cannot discard with idx = '{}' which is out of range of hand size = '{}'",
-                        idx,
-                        p.hand_len()
-                    )));
-                }
-
                 let (removed_card, _) = p.remove_card(idx);
 
                 // Discard and get a hint back
                 self.discard.push(removed_card);
 
-                self.hints += 1;
+                let regained_hint = self.hints < self.config.max_hints;
+                if regained_hint {
+                    self.hints += 1;
+                }
 
                 // Pickup another card
-                let new_card = self.deck.pop_front().expect("Deck somehow is empty");
-                p.push_card(new_card);
+                let drawn = self.draw_card(requester_pub_id);
+
+                ActionKind::Discard {
+                    slot: idx,
+                    card: removed_card,
+                    regained_hint,
+                    drawn,
+                }
             }
             HanabiMove::Play(idx) => {
                 // Remove
-                let (removed_card, _) = {
-                    let p = &mut self.players[requester_pub_id as usize];
-                    if idx >= p.hand_len() {
-                        return Err(HanabiError::InvalidMove(format!(
-                            "Cannot play with idx = '{}' which is out of range of hand size = '{}'",
-                            idx,
-                            p.hand_len()
-                        )));
-                    }
-
-                    p.remove_card(idx)
-                };
+                let (removed_card, _) = self.players[requester_pub_id as usize].remove_card(idx);
 
                 // Play if playable, else discard
-                if self.card_playable(&removed_card) {
+                let success = self.card_playable(&removed_card);
+                if success {
                     self.board.push(removed_card);
                 } else {
-                    self.bombs += 1;
+                    self.strikes += 1;
                     self.discard.push(removed_card);
                 }
 
                 // Pickup another card
-                {
-                    let p = &mut self.players[requester_pub_id as usize];
-                    let new_card = self.deck.pop_front().expect("Deck somehow is empty");
-                    p.push_card(new_card);
+                let drawn = self.draw_card(requester_pub_id);
+
+                ActionKind::Play {
+                    slot: idx,
+                    card: removed_card,
+                    success,
+                    drawn,
+                }
+            }
+        };
+
+        let action = Action {
+            player: requester_pub_id,
+            kind,
+        };
+        self.log.push(action.clone());
+        self.undo_stack.push(undo);
+        self.end_turn();
+
+        Ok(action)
+    }
+
+    /**
+     * @brief Take back the last num_moves moves, restoring the hands (order and knowledge),
+     * board, discard, counters and deck exactly as they were.
+     *
+     * @return The undone actions, most recent first
+     */
+    pub fn undo(&mut self, num_moves: usize) -> Result<Vec<Action>, HanabiError> {
+        if num_moves > self.undo_stack.len() {
+            return Err(HanabiError::OutOfBounds {
+                idx: num_moves,
+                limits: Limits {
+                    lo: 0,
+                    hi: self.undo_stack.len(),
+                },
+            });
+        }
+
+        let mut undone = Vec::with_capacity(num_moves);
+        for _ in 0..num_moves {
+            let action = self.log.pop().expect("log and undo stack out of sync");
+            let undo = self
+                .undo_stack
+                .pop()
+                .expect("log and undo stack out of sync");
+            self.undo_action(&action, undo);
+            undone.push(action);
+        }
+
+        Ok(undone)
+    }
+
+    fn undo_action(&mut self, action: &Action, undo: Undo) {
+        let player = &mut self.players[action.player as usize];
+
+        // Put the drawn card back on top of the deck
+        if let Some(drawn) = action.drawn() {
+            player.pop_card();
+            self.deck.push_front(drawn);
+        }
+
+        match action.kind {
+            ActionKind::Hint { target, .. } => {
+                self.players[target as usize].set_knowledge(undo.knowledge);
+                self.hints += 1;
+            }
+            ActionKind::Discard {
+                slot,
+                card,
+                regained_hint,
+                ..
+            } => {
+                player.insert_card(slot, card, undo.knowledge[0].clone());
+                self.discard.pop();
+                if regained_hint {
+                    self.hints -= 1;
+                }
+            }
+            ActionKind::Play {
+                slot,
+                card,
+                success,
+                ..
+            } => {
+                player.insert_card(slot, card, undo.knowledge[0].clone());
+                if success {
+                    self.board.pop();
+                } else {
+                    self.discard.pop();
+                    self.strikes -= 1;
                 }
             }
         }
 
-        Ok(())
+        self.turns_since_last_pickup = undo.turns_since_last_pickup;
+        self.active_player = action.player;
+    }
+}
+
+/**
+ * @brief Set up a Game from an exact scenario instead of a shuffled deal.
+ *
+ * The deck is used in the order given: the front of the deck is drawn first, and unless explicit
+ * hands are provided the opening hands are dealt from it round-robin, exactly like a normal deal
+ * (so with 2 players, deck[0] lands in slot 0 of player 0 and deck[1] in slot 0 of player 1).
+ * Everything is validated against the GameConfig when the Game is built.
+ */
+pub struct GameBuilder {
+    config: GameConfig,
+    deck: VecDeque<Card>,
+    hands: Option<Vec<Vec<Card>>>,
+    board: Vec<Card>,
+    discard: Vec<Card>,
+    hints: u8,
+    strikes: u8,
+    seed: Option<u64>,
+}
+
+impl GameBuilder {
+    pub fn new(config: GameConfig, deck: VecDeque<Card>) -> Self {
+        GameBuilder {
+            deck,
+            hands: None,
+            board: Vec::new(),
+            discard: Vec::new(),
+            hints: config.max_hints,
+            strikes: 0,
+            seed: None,
+            config,
+        }
+    }
+
+    /**
+     * @brief Give every player an explicit starting hand (slot 0 first) instead of dealing
+     */
+    pub fn hands(mut self, hands: Vec<Vec<Card>>) -> Self {
+        self.hands = Some(hands);
+        self
+    }
+
+    /**
+     * @brief Cards already played, in the order they were played
+     */
+    pub fn board(mut self, board: Vec<Card>) -> Self {
+        self.board = board;
+        self
+    }
+
+    pub fn discard(mut self, discard: Vec<Card>) -> Self {
+        self.discard = discard;
+        self
+    }
+
+    pub fn hints(mut self, hints: u8) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    pub fn strikes(mut self, strikes: u8) -> Self {
+        self.strikes = strikes;
+        self
+    }
+
+    /**
+     * @brief Seed used to generate the player UIDs, for fully reproducible scenarios
+     */
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<Game, HanabiError> {
+        let config = &self.config;
+        config.validate()?;
+
+        if self.hints > config.max_hints {
+            return Err(HanabiError::TooManyHints {
+                hints: self.hints,
+                max_hints: config.max_hints,
+            });
+        }
+        if self.strikes >= config.max_strikes {
+            return Err(HanabiError::TooManyStrikes {
+                strikes: self.strikes,
+                max_strikes: config.max_strikes,
+            });
+        }
+
+        match &self.hands {
+            Some(hands) => {
+                if hands.len() != config.num_players {
+                    return Err(HanabiError::WrongNumberOfHands {
+                        hands: hands.len(),
+                        num_players: config.num_players,
+                    });
+                }
+                if let Some(hand) = hands.iter().find(|hand| hand.len() > config.hand_size) {
+                    return Err(HanabiError::HandTooBig {
+                        len: hand.len(),
+                        hand_size: config.hand_size,
+                    });
+                }
+            }
+            None => {
+                if self.deck.len() < config.hand_size * config.num_players {
+                    return Err(HanabiError::DeckTooSmall {
+                        deck_len: self.deck.len(),
+                        needed: config.hand_size * config.num_players,
+                    });
+                }
+            }
+        }
+
+        // Every stack on the board must have been built up from a 1 without gaps
+        for (idx, card) in self.board.iter().enumerate() {
+            if number_below(card.number())
+                != self.board[..idx]
+                    .iter()
+                    .filter(|c| c.color() == card.color())
+                    .map(|c| c.number())
+                    .max()
+            {
+                return Err(HanabiError::NotOnBoard(*card));
+            }
+        }
+
+        // No card can show up more often than it exists in the configured deck
+        let mut remaining: Vec<Card> = config.generate_deck().into_iter().collect();
+        let all_cards = self
+            .deck
+            .iter()
+            .chain(self.hands.iter().flatten().flatten())
+            .chain(self.board.iter())
+            .chain(self.discard.iter());
+        for card in all_cards {
+            match remaining.iter().position(|c| c == card) {
+                Some(idx) => {
+                    remaining.swap_remove(idx);
+                }
+                None => {
+                    return Err(HanabiError::TooManyCopies(*card));
+                }
+            }
+        }
+
+        let players = match self.seed {
+            Some(seed) => generate_players(config.num_players, &mut StdRng::seed_from_u64(seed)),
+            None => generate_players(config.num_players, &mut thread_rng()),
+        };
+
+        let mut game = Game {
+            deck: self.deck,
+            discard: self.discard,
+            board: self.board,
+            players,
+            active_player: 0,
+            hints: self.hints,
+            strikes: self.strikes,
+            turns_since_last_pickup: None,
+            log: Vec::new(),
+            undo_stack: Vec::new(),
+            config: self.config,
+        };
+
+        match self.hands {
+            Some(hands) => {
+                let knowledge = game.fresh_knowledge();
+                for (player, hand) in game.players.iter_mut().zip(hands) {
+                    for card in hand {
+                        player.push_card_with_knowledge(card, knowledge.clone());
+                    }
+                }
+            }
+            None => game.deal_cards(),
+        }
+        game.start_final_round_if_deck_empty();
+
+        Ok(game)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::card::COLORS;
+    use crate::rules::{GameOverReason, StrikeoutScoring, MAX_HINTS, MAX_STRIKES};
 
     #[test]
     fn test_shuffle_deck() {
@@ -349,7 +854,7 @@ mod tests {
         let deck = generate_normal_deck();
         let before_len = deck.len();
 
-        let shuffled = shuffle_deck(deck);
+        let shuffled = shuffle_deck(deck, &mut thread_rng());
         let after_len = shuffled.len();
 
         assert!(before_len == after_len);
@@ -359,7 +864,7 @@ mod tests {
         use crate::rules::generate_normal_deck;
 
         let mut deck = generate_normal_deck();
-        deck = shuffle_deck(deck);
+        deck = shuffle_deck(deck, &mut thread_rng());
 
         let deck_len = deck.len();
 
@@ -367,12 +872,15 @@ mod tests {
             deck,
             discard: Vec::new(),
             board: Vec::new(),
-            players: generate_players(num_players),
+            players: generate_players(num_players, &mut thread_rng()),
             active_player: 0,
             hints: MAX_HINTS,
-            bombs: NUM_BOMBS,
+            strikes: 0,
             //turn_number: 0,
             turns_since_last_pickup: None,
+            log: Vec::new(),
+            undo_stack: Vec::new(),
+            config: GameConfig::standard(num_players).unwrap(),
         };
 
         game.deal_cards();
@@ -411,4 +919,1086 @@ mod tests {
         let num_players = 5;
         deal_cards_for_num_players(num_players);
     }
+
+    /**
+     * @brief Build a game with an unshuffled deck so that tests know exactly who holds what.
+     */
+    fn unshuffled_game(num_players: usize, deck: VecDeque<Card>) -> Game {
+        let mut game = Game {
+            deck,
+            discard: Vec::new(),
+            board: Vec::new(),
+            players: generate_players(num_players, &mut thread_rng()),
+            active_player: 0,
+            hints: MAX_HINTS,
+            strikes: 0,
+            turns_since_last_pickup: None,
+            log: Vec::new(),
+            undo_stack: Vec::new(),
+            config: GameConfig::standard(num_players).unwrap(),
+        };
+
+        game.deal_cards();
+        game
+    }
+
+    fn red_ones(count: usize) -> VecDeque<Card> {
+        use crate::card::{Color, Number};
+
+        (0..count)
+            .map(|_| Card::new(Color::Red, Number::One))
+            .collect()
+    }
+
+    #[test]
+    fn test_turn_passes_to_next_player() {
+        use crate::card::Number;
+
+        let mut game = unshuffled_game(3, red_ones(20));
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        assert_eq!(game.active_player(), 0);
+        assert_eq!(game.uid_of(2).unwrap(), uids[2]);
+
+        // Player 1 can't go before player 0
+        let mv = HanabiMove::Hint((0, Hint::NumberHint(Number::One)));
+        assert!(matches!(
+            game.play_move(mv, uids[1]),
+            Err(HanabiError::NotYourTurn {
+                requested: 1,
+                active: 0
+            })
+        ));
+
+        let mv = HanabiMove::Hint((1, Hint::NumberHint(Number::One)));
+        game.play_move(mv, uids[0]).unwrap();
+        assert_eq!(game.active_player(), 1);
+
+        let mv = HanabiMove::Hint((2, Hint::NumberHint(Number::One)));
+        game.play_move(mv, uids[1]).unwrap();
+        assert_eq!(game.active_player(), 2);
+
+        let mv = HanabiMove::Hint((0, Hint::NumberHint(Number::One)));
+        game.play_move(mv, uids[2]).unwrap();
+        assert_eq!(game.active_player(), 0);
+    }
+
+    #[test]
+    fn test_move_errors() {
+        use crate::card::Color;
+
+        let mut game = unshuffled_game(2, red_ones(20));
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+        let unknown = uids[0] ^ uids[1] ^ 1;
+
+        assert!(matches!(
+            game.play_move(HanabiMove::Play(0), unknown),
+            Err(HanabiError::UnknownPlayer(uid)) if uid == unknown
+        ));
+        assert!(matches!(
+            game.play_move(HanabiMove::Play(5), uids[0]),
+            Err(HanabiError::IndexOutOfRange {
+                idx: 5,
+                hand_len: 5
+            })
+        ));
+        assert!(matches!(
+            game.play_move(HanabiMove::Hint((2, Hint::ColorHint(Color::Red))), uids[0]),
+            Err(HanabiError::OutOfBounds {
+                idx: 2,
+                limits: Limits { lo: 0, hi: 1 }
+            })
+        ));
+        assert!(matches!(
+            game.get_player_cards(uids[0], 0),
+            Err(HanabiError::OwnCards(0))
+        ));
+        assert!(matches!(
+            game.get_player_knowledge(3),
+            Err(HanabiError::UnknownPublicId(3))
+        ));
+    }
+
+    /**
+     * @brief 2 player game where player 0 holds r1 and b2, and player 1 holds g3 and y4
+     */
+    fn rules_game(hints: u8) -> (Game, Vec<UID>) {
+        use crate::card::{Color, Number};
+
+        let hands = vec![
+            vec![
+                Card::new(Color::Red, Number::One),
+                Card::new(Color::Blue, Number::Two),
+            ],
+            vec![
+                Card::new(Color::Green, Number::Three),
+                Card::new(Color::Yellow, Number::Four),
+            ],
+        ];
+        let deck = vec![Card::new(Color::White, Number::Five)].into();
+        let game = GameBuilder::new(GameConfig::standard(2).unwrap(), deck)
+            .hands(hands)
+            .hints(hints)
+            .build()
+            .unwrap();
+        let uids = game.players.iter().map(|p| p.uid).collect();
+
+        (game, uids)
+    }
+
+    /**
+     * @brief Rejected moves must leave the game exactly as it was
+     */
+    fn assert_untouched(game: &Game, hints: u8) {
+        assert_eq!(game.active_player(), 0);
+        assert_eq!(game.hints, hints);
+        assert!(game.log().is_empty());
+        assert_eq!(game.deck_len(), 1);
+        assert_eq!(game.players[0].hand_len(), 2);
+        assert_eq!(
+            game.get_player_knowledge(1).unwrap(),
+            vec![CardKnowledge::for_suits(&COLORS); 2]
+        );
+    }
+
+    #[test]
+    fn test_rule_no_hint_without_tokens() {
+        use crate::card::Color;
+
+        let (mut game, uids) = rules_game(0);
+
+        let mv = HanabiMove::Hint((1, Hint::ColorHint(Color::Green)));
+        assert!(matches!(
+            game.play_move(mv, uids[0]),
+            Err(HanabiError::NoHintsLeft)
+        ));
+        assert_untouched(&game, 0);
+
+        // Plays and discards are still fine
+        assert!(game.legal_move(&HanabiMove::Play(0), 0).is_ok());
+        assert!(game.legal_move(&HanabiMove::Discard(0), 0).is_ok());
+    }
+
+    #[test]
+    fn test_rule_no_discard_at_max_hints() {
+        let (mut game, uids) = rules_game(MAX_HINTS);
+
+        assert!(matches!(
+            game.play_move(HanabiMove::Discard(0), uids[0]),
+            Err(HanabiError::CannotDiscardAtMaxHints {
+                max_hints: MAX_HINTS
+            })
+        ));
+        assert_untouched(&game, MAX_HINTS);
+
+        // One hint down, discarding is allowed again
+        let (mut game, uids) = rules_game(MAX_HINTS - 1);
+        game.play_move(HanabiMove::Discard(0), uids[0]).unwrap();
+        assert_eq!(game.hints, MAX_HINTS);
+    }
+
+    #[test]
+    fn test_rule_index_must_be_in_hand() {
+        let (mut game, uids) = rules_game(MAX_HINTS - 1);
+
+        for mv in [HanabiMove::Play(2), HanabiMove::Discard(2)].iter() {
+            assert!(matches!(
+                game.play_move(*mv, uids[0]),
+                Err(HanabiError::IndexOutOfRange {
+                    idx: 2,
+                    hand_len: 2
+                })
+            ));
+            assert_untouched(&game, MAX_HINTS - 1);
+        }
+    }
+
+    #[test]
+    fn test_rule_hint_must_touch_a_card() {
+        use crate::card::{Color, Number};
+
+        let (mut game, uids) = rules_game(MAX_HINTS);
+
+        let hint = Hint::ColorHint(Color::Red);
+        assert!(matches!(
+            game.play_move(HanabiMove::Hint((1, hint)), uids[0]),
+            Err(HanabiError::HintTouchesNoCards { target: 1, hint: h }) if h == hint
+        ));
+        assert_untouched(&game, MAX_HINTS);
+
+        let hint = Hint::NumberHint(Number::One);
+        assert!(matches!(
+            game.play_move(HanabiMove::Hint((1, hint)), uids[0]),
+            Err(HanabiError::HintTouchesNoCards { target: 1, hint: h }) if h == hint
+        ));
+        assert_untouched(&game, MAX_HINTS);
+        assert_eq!(
+            HanabiError::HintTouchesNoCards { target: 1, hint }.to_string(),
+            "A 1 hint doesn't touch any of player 1's cards"
+        );
+
+        let mv = HanabiMove::Hint((1, Hint::NumberHint(Number::Four)));
+        game.play_move(mv, uids[0]).unwrap();
+        assert_eq!(game.hints, MAX_HINTS - 1);
+    }
+
+    #[test]
+    fn test_rule_no_self_hint() {
+        use crate::card::Color;
+
+        let (mut game, uids) = rules_game(MAX_HINTS);
+
+        let mv = HanabiMove::Hint((0, Hint::ColorHint(Color::Red)));
+        assert!(matches!(
+            game.play_move(mv, uids[0]),
+            Err(HanabiError::SelfHint(0))
+        ));
+        assert_untouched(&game, MAX_HINTS);
+        assert_eq!(
+            game.get_player_knowledge(0).unwrap(),
+            vec![CardKnowledge::for_suits(&COLORS); 2]
+        );
+    }
+
+    #[test]
+    fn test_hint_consumes_token() {
+        use crate::card::Color;
+
+        let mut game = unshuffled_game(2, red_ones(20));
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        let mv = HanabiMove::Hint((1, Hint::ColorHint(Color::Red)));
+        game.play_move(mv, uids[0]).unwrap();
+        assert_eq!(game.hints, MAX_HINTS - 1);
+
+        let mv = HanabiMove::Hint((0, Hint::ColorHint(Color::Red)));
+        game.play_move(mv, uids[1]).unwrap();
+        assert_eq!(game.hints, MAX_HINTS - 2);
+    }
+
+    #[test]
+    fn test_final_round_after_last_pickup() {
+        use crate::card::Color;
+
+        // 10 cards are dealt, leaving exactly one in the deck
+        let mut game = unshuffled_game(2, red_ones(11));
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+        game.hints = 1;
+
+        // Player 0 draws the last card
+        game.play_move(HanabiMove::Discard(0), uids[0]).unwrap();
+        assert!(game.deck.is_empty());
+        assert_eq!(game.turns_since_last_pickup, Some(0));
+        assert!(matches!(game.finished(), GameResultState::InProgress));
+
+        // Everyone, including player 0, gets one more turn
+        let mv = HanabiMove::Hint((0, Hint::ColorHint(Color::Red)));
+        game.play_move(mv, uids[1]).unwrap();
+        assert!(matches!(game.finished(), GameResultState::InProgress));
+
+        let mv = HanabiMove::Hint((1, Hint::ColorHint(Color::Red)));
+        game.play_move(mv, uids[0]).unwrap();
+        assert!(matches!(
+            game.finished(),
+            GameResultState::Finished(GameOverReason::DeckExhausted, _)
+        ));
+
+        let mv = HanabiMove::Hint((0, Hint::ColorHint(Color::Red)));
+        assert!(matches!(
+            game.play_move(mv, uids[1]),
+            Err(HanabiError::GameFinished)
+        ));
+    }
+
+    #[test]
+    fn test_play_and_discard_with_empty_deck() {
+        // 10 cards are dealt, leaving exactly one in the deck
+        let mut game = unshuffled_game(2, red_ones(11));
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+        game.hints = 1;
+
+        // Player 0 draws the last card and keeps a full hand
+        game.play_move(HanabiMove::Play(0), uids[0]).unwrap();
+        assert_eq!(game.players[0].hand_len(), 5);
+        assert!(game.deck.is_empty());
+
+        // From here on, hands shrink instead of panicking
+        game.play_move(HanabiMove::Discard(0), uids[1]).unwrap();
+        assert_eq!(game.players[1].hand_len(), 4);
+
+        game.play_move(HanabiMove::Play(4), uids[0]).unwrap();
+        assert_eq!(game.players[0].hand_len(), 4);
+
+        assert!(matches!(
+            game.finished(),
+            GameResultState::Finished(GameOverReason::DeckExhausted, _)
+        ));
+    }
+
+    fn strike_out(game: &mut Game) {
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        // Every card is a red one, so only the very first play succeeds
+        for turn in 0..=MAX_STRIKES as usize {
+            assert!(matches!(game.finished(), GameResultState::InProgress));
+            game.play_move(HanabiMove::Play(0), uids[turn % uids.len()])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_strike_out_scores_zero() {
+        let mut game = unshuffled_game(2, red_ones(20));
+        strike_out(&mut game);
+
+        assert_eq!(game.strikes, MAX_STRIKES);
+        assert_eq!(game.strikes_left(), 0);
+        assert_eq!(
+            game.finished(),
+            GameResultState::Finished(GameOverReason::StruckOut, 0)
+        );
+    }
+
+    #[test]
+    fn test_strike_out_keeps_points() {
+        let mut game = unshuffled_game(2, red_ones(20));
+        game.config.strikeout_scoring = StrikeoutScoring::KeepPoints;
+        strike_out(&mut game);
+
+        assert_eq!(
+            game.finished(),
+            GameResultState::Finished(GameOverReason::StruckOut, 1)
+        );
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        use crate::rules::generate_normal_deck;
+
+        let a =
+            Game::from_seed(GameConfig::standard(4).unwrap(), generate_normal_deck(), 42).unwrap();
+        let b =
+            Game::from_seed(GameConfig::standard(4).unwrap(), generate_normal_deck(), 42).unwrap();
+        let c =
+            Game::from_seed(GameConfig::standard(4).unwrap(), generate_normal_deck(), 43).unwrap();
+
+        assert_eq!(a.deck, b.deck);
+        assert!(a.players == b.players);
+
+        assert_ne!(a.deck, c.deck);
+        assert!(a.players != c.players);
+    }
+
+    /**
+     * @brief A normal deck, rearranged so that the given cards are drawn first (in order)
+     */
+    fn deck_starting_with(cards: Vec<Card>) -> VecDeque<Card> {
+        use crate::rules::generate_normal_deck;
+
+        let mut rest = generate_normal_deck();
+        for card in &cards {
+            let idx = rest.iter().position(|c| c == card).unwrap();
+            rest.remove(idx);
+        }
+
+        cards.into_iter().chain(rest).collect()
+    }
+
+    #[test]
+    fn test_builder_deals_in_deck_order() {
+        use crate::card::{Color, Number};
+
+        let deck = deck_starting_with(vec![
+            Card::new(Color::Blue, Number::One),
+            Card::new(Color::Red, Number::Five),
+        ]);
+        let expected_len = deck.len() - 10;
+
+        let game = GameBuilder::new(GameConfig::standard(2).unwrap(), deck)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            game.players[0].hand_at(0).0,
+            Card::new(Color::Blue, Number::One)
+        );
+        assert_eq!(
+            game.players[1].hand_at(0).0,
+            Card::new(Color::Red, Number::Five)
+        );
+        assert_eq!(game.deck.len(), expected_len);
+    }
+
+    #[test]
+    fn test_builder_explicit_state() {
+        use crate::card::{Color, Number};
+
+        let hands = vec![
+            vec![
+                Card::new(Color::Red, Number::Two),
+                Card::new(Color::Blue, Number::Five),
+            ],
+            vec![Card::new(Color::Green, Number::One)],
+        ];
+        let deck: VecDeque<Card> = vec![Card::new(Color::White, Number::Three)].into();
+
+        let mut game = GameBuilder::new(GameConfig::standard(2).unwrap(), deck)
+            .hands(hands)
+            .board(vec![Card::new(Color::Red, Number::One)])
+            .discard(vec![Card::new(Color::Yellow, Number::Four)])
+            .hints(2)
+            .strikes(1)
+            .seed(7)
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        assert_eq!(game.hints, 2);
+        assert_eq!(game.strikes, 1);
+        assert_eq!(game.players[1].hand_len(), 1);
+
+        game.play_move(HanabiMove::Play(0), uids[0]).unwrap();
+        assert_eq!(game.board.len(), 2);
+        assert_eq!(game.strikes, 1);
+        assert_eq!(
+            game.players[0].hand_at(1).0,
+            Card::new(Color::White, Number::Three)
+        );
+    }
+
+    #[test]
+    fn test_builder_with_empty_deck() {
+        use crate::card::{Color, Number};
+
+        let hands = vec![
+            vec![Card::new(Color::Red, Number::One)],
+            vec![Card::new(Color::Blue, Number::One)],
+        ];
+        let mut game = GameBuilder::new(GameConfig::standard(2).unwrap(), VecDeque::new())
+            .hands(hands)
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        // Nothing left to draw, so the final round has already started: one turn each
+        assert_eq!(game.turns_since_last_pickup, Some(0));
+        game.play_move(HanabiMove::Play(0), uids[0]).unwrap();
+        assert!(matches!(game.finished(), GameResultState::InProgress));
+        game.play_move(HanabiMove::Play(0), uids[1]).unwrap();
+        assert_eq!(
+            game.finished(),
+            GameResultState::Finished(GameOverReason::DeckExhausted, 2)
+        );
+
+        // Nobody has any moves left, whether asking the game or looking at their own view
+        for &uid in &uids {
+            assert!(game.legal_moves(uid).unwrap().is_empty());
+            assert!(game.view_for(uid).unwrap().legal_moves().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_builder_validates_scenario() {
+        use crate::card::{Color, Number};
+        use crate::rules::generate_normal_deck;
+
+        // There is only one red five
+        let mut deck = generate_normal_deck();
+        deck.push_back(Card::new(Color::Red, Number::Five));
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), deck).build(),
+            Err(HanabiError::TooManyCopies(_))
+        ));
+
+        // Stacks can't skip a number
+        let board = vec![Card::new(Color::Red, Number::Two)];
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), red_ones(0))
+                .hands(vec![vec![], vec![]])
+                .board(board)
+                .build(),
+            Err(HanabiError::NotOnBoard(_))
+        ));
+
+        // Not enough cards for a deal
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), red_ones(3)).build(),
+            Err(HanabiError::DeckTooSmall { .. })
+        ));
+
+        // Wrong number of hands, or hands that are too big
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(3).unwrap(), red_ones(0))
+                .hands(vec![vec![], vec![]])
+                .build(),
+            Err(HanabiError::WrongNumberOfHands {
+                hands: 2,
+                num_players: 3
+            })
+        ));
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), VecDeque::new())
+                .hands(vec![
+                    deck_starting_with(vec![]).into_iter().take(6).collect(),
+                    vec![]
+                ])
+                .build(),
+            Err(HanabiError::HandTooBig {
+                len: 6,
+                hand_size: 5
+            })
+        ));
+
+        // Counters must be within the rules
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), generate_normal_deck())
+                .hints(MAX_HINTS + 1)
+                .build(),
+            Err(HanabiError::TooManyHints { .. })
+        ));
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), generate_normal_deck())
+                .strikes(MAX_STRIKES)
+                .build(),
+            Err(HanabiError::TooManyStrikes { .. })
+        ));
+    }
+
+    #[test]
+    fn test_action_log() {
+        use crate::card::{Color, Number};
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let b1 = Card::new(Color::Blue, Number::One);
+        let g3 = Card::new(Color::Green, Number::Three);
+        let y4 = Card::new(Color::Yellow, Number::Four);
+        let w5 = Card::new(Color::White, Number::Five);
+
+        let mut game = GameBuilder::new(GameConfig::standard(2).unwrap(), vec![y4, w5].into())
+            .hands(vec![vec![r1, g3], vec![b1, g3]])
+            .hints(1)
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        game.play_move(HanabiMove::Play(0), uids[0]).unwrap();
+        game.play_move(HanabiMove::Discard(1), uids[1]).unwrap();
+        let mv = HanabiMove::Hint((1, Hint::NumberHint(Number::Five)));
+        game.play_move(mv, uids[0]).unwrap();
+
+        assert_eq!(
+            game.log(),
+            &[
+                Action {
+                    player: 0,
+                    kind: ActionKind::Play {
+                        slot: 0,
+                        card: r1,
+                        success: true,
+                        drawn: Some(y4),
+                    },
+                },
+                Action {
+                    player: 1,
+                    kind: ActionKind::Discard {
+                        slot: 1,
+                        card: g3,
+                        regained_hint: true,
+                        drawn: Some(w5),
+                    },
+                },
+                Action {
+                    player: 0,
+                    kind: ActionKind::Hint {
+                        target: 1,
+                        hint: Hint::NumberHint(Number::Five),
+                        touched: vec![1],
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_play_move_outcome() {
+        use crate::card::{Color, Number};
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let r2 = Card::new(Color::Red, Number::Two);
+        let b4 = Card::new(Color::Blue, Number::Four);
+        let g1 = Card::new(Color::Green, Number::One);
+
+        let mut game = GameBuilder::new(GameConfig::standard(2).unwrap(), vec![g1].into())
+            .hands(vec![vec![r2, r1], vec![b4, r1]])
+            .hints(MAX_HINTS - 1)
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        // A misplay reveals the card and draws the last one from the deck
+        let outcome = game.play_move(HanabiMove::Play(0), uids[0]).unwrap();
+        assert_eq!(
+            outcome.kind,
+            ActionKind::Play {
+                slot: 0,
+                card: r2,
+                success: false,
+                drawn: Some(g1),
+            }
+        );
+        assert_eq!(outcome.drawn(), Some(g1));
+        assert_eq!(outcome.seen_by(1).drawn(), Some(g1));
+        assert_eq!(outcome.seen_by(0).drawn(), None);
+        let mover_view = game.view_for(uids[0]).unwrap();
+        assert_eq!(mover_view.log.last(), Some(&outcome.seen_by(0)));
+        assert_eq!(mover_view.log.last().unwrap().drawn(), None);
+
+        // Hints report the slots they touched
+        let mv = HanabiMove::Hint((0, Hint::ColorHint(Color::Red)));
+        let outcome = game.play_move(mv, uids[1]).unwrap();
+        assert_eq!(
+            outcome.kind,
+            ActionKind::Hint {
+                target: 0,
+                hint: Hint::ColorHint(Color::Red),
+                touched: vec![0],
+            }
+        );
+
+        // Discarding regains the hint that was just spent
+        let outcome = game.play_move(HanabiMove::Discard(1), uids[0]).unwrap();
+        assert_eq!(
+            outcome.kind,
+            ActionKind::Discard {
+                slot: 1,
+                card: g1,
+                regained_hint: true,
+                drawn: None,
+            }
+        );
+        assert_eq!(game.hints, MAX_HINTS - 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use crate::rules::generate_normal_deck;
+
+        let mut game =
+            Game::from_seed(GameConfig::standard(3).unwrap(), generate_normal_deck(), 3).unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        game.play_move(HanabiMove::Play(2), uids[0]).unwrap();
+        let mv = HanabiMove::Hint((0, Hint::ColorHint(game.players[0].hand_at(0).0.color())));
+        game.play_move(mv, uids[1]).unwrap();
+
+        let json = serde_json::to_string(&game).unwrap();
+        let mut restored: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        assert_eq!(restored.log(), game.log());
+        assert_eq!(
+            restored.get_player_knowledge(0).unwrap(),
+            game.get_player_knowledge(0).unwrap()
+        );
+
+        // Both copies carry on identically
+        let a = game.play_move(HanabiMove::Discard(0), uids[2]).unwrap();
+        let b = restored.play_move(HanabiMove::Discard(0), uids[2]).unwrap();
+        assert_eq!(a, b);
+
+        // Knowledge that no card could ever have is rejected
+        let bad = serde_json::from_str::<crate::card::CardKnowledge>(
+            r#"{"not_these_colors":4096,"not_these_numbers":0}"#,
+        );
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn test_view_for() {
+        use crate::card::{Color, Number};
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let b4 = Card::new(Color::Blue, Number::Four);
+        let g1 = Card::new(Color::Green, Number::One);
+
+        let mut game = GameBuilder::new(GameConfig::standard(3).unwrap(), vec![g1].into())
+            .hands(vec![vec![r1], vec![b4], vec![r1]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        game.play_move(HanabiMove::Play(0), uids[0]).unwrap();
+
+        let view = game.view_for(uids[0]).unwrap();
+        assert_eq!(view.public_id, 0);
+        assert!(!view.is_my_turn());
+        assert_eq!(view.hand_len(), 1);
+        assert_eq!(view.board, vec![r1]);
+        assert_eq!(view.deck_len, 0);
+        assert_eq!(view.active_player, 1);
+        assert_eq!(view.others.len(), 2);
+        assert_eq!(view.other(1).unwrap().cards, vec![b4.view()]);
+        assert!(view.other(0).is_none());
+
+        // Player 0 doesn't get to see what they drew, but everyone else does
+        assert_eq!(view.log[0].drawn(), None);
+        assert_eq!(view.other(2).unwrap().cards, vec![r1.view()]);
+        let view = game.view_for(uids[1]).unwrap();
+        assert!(view.is_my_turn());
+        assert_eq!(view.log[0].drawn(), Some(g1));
+        assert_eq!(view.other(0).unwrap().cards, vec![g1.view()]);
+
+        let unknown = uids[0] ^ uids[1] ^ uids[2] ^ 1;
+        assert!(matches!(
+            game.view_for(unknown),
+            Err(HanabiError::UnknownPlayer(uid)) if uid == unknown
+        ));
+    }
+
+    #[test]
+    fn test_spectator_view() {
+        use crate::card::{Color, Number};
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let b4 = Card::new(Color::Blue, Number::Four);
+        let g1 = Card::new(Color::Green, Number::One);
+        let w2 = Card::new(Color::White, Number::Two);
+
+        let mut game = GameBuilder::new(GameConfig::standard(2).unwrap(), vec![g1, w2].into())
+            .hands(vec![vec![r1, b4], vec![b4]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        let mv = HanabiMove::Hint((1, Hint::NumberHint(Number::Four)));
+        game.play_move(mv, uids[0]).unwrap();
+
+        let view = game.spectator_view(false);
+        assert_eq!(view.hands.len(), 2);
+        assert_eq!(view.hands[0].cards[0].0, r1);
+        assert_eq!(view.hands[1].cards[0].0, b4);
+        assert_eq!(
+            view.hands[1].cards[0].1,
+            game.get_player_knowledge(1).unwrap()[0]
+        );
+        assert_eq!(view.deck_len, 2);
+        assert_eq!(view.deck, None);
+        assert_eq!(view.log, game.log());
+
+        let view = game.spectator_view(true);
+        assert_eq!(view.deck, Some(vec![g1, w2]));
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        use crate::card::{Color, Number};
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let r2 = Card::new(Color::Red, Number::Two);
+        let b2 = Card::new(Color::Blue, Number::Two);
+        let g4 = Card::new(Color::Green, Number::Four);
+
+        let mut game = GameBuilder::new(GameConfig::standard(3).unwrap(), VecDeque::new())
+            .hands(vec![vec![r1, g4], vec![r2, b2, r1], vec![g4]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        // All hints available, so no discards
+        let moves = game.legal_moves(uids[0]).unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                HanabiMove::Play(0),
+                HanabiMove::Play(1),
+                HanabiMove::Hint((1, Hint::ColorHint(Color::Red))),
+                HanabiMove::Hint((1, Hint::ColorHint(Color::Blue))),
+                HanabiMove::Hint((1, Hint::NumberHint(Number::Two))),
+                HanabiMove::Hint((1, Hint::NumberHint(Number::One))),
+                HanabiMove::Hint((2, Hint::ColorHint(Color::Green))),
+                HanabiMove::Hint((2, Hint::NumberHint(Number::Four))),
+            ]
+        );
+        for mv in &moves {
+            assert!(game.legal_move(mv, 0).is_ok());
+        }
+        assert_eq!(game.view_for(uids[0]).unwrap().legal_moves(), moves);
+
+        // Not your turn
+        assert!(game.legal_moves(uids[1]).unwrap().is_empty());
+        assert!(game.view_for(uids[1]).unwrap().legal_moves().is_empty());
+
+        // No hints left, so only plays and discards
+        game.hints = 0;
+        assert_eq!(
+            game.legal_moves(uids[0]).unwrap(),
+            vec![
+                HanabiMove::Play(0),
+                HanabiMove::Play(1),
+                HanabiMove::Discard(0),
+                HanabiMove::Discard(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_config() {
+        let mut config = GameConfig::standard(5).unwrap();
+        config.num_players = 6;
+        config.hand_size = 3;
+        config.max_hints = 4;
+        config.max_strikes = 1;
+        config.card_frequencies = [2, 2, 2, 2, 2];
+
+        let deck = config.generate_deck();
+        assert_eq!(deck.len(), 50);
+
+        let game = Game::new(config, deck).unwrap();
+        assert_eq!(game.players.len(), 6);
+        assert!(game.players.iter().all(|p| p.hand_len() == 3));
+        assert_eq!(game.hints, 4);
+        assert_eq!(game.strikes_left(), 1);
+
+        // Not enough cards to go around
+        let mut config = GameConfig::standard(2).unwrap();
+        config.card_frequencies = [1, 0, 0, 0, 0];
+        assert_eq!(config.max_score(), 5);
+        assert!(matches!(
+            config.validate(),
+            Err(HanabiError::DeckTooSmall {
+                deck_len: 5,
+                needed: 10
+            })
+        ));
+
+        assert!(matches!(
+            GameConfig::standard(1),
+            Err(HanabiError::InvalidPlayerCount { num_players: 1, .. })
+        ));
+        assert!(matches!(
+            GameConfig::standard(6),
+            Err(HanabiError::InvalidPlayerCount { num_players: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn test_new_with_short_deck() {
+        assert!(matches!(
+            Game::new(GameConfig::standard(2).unwrap(), red_ones(9)),
+            Err(HanabiError::DeckTooSmall {
+                deck_len: 9,
+                needed: 10
+            })
+        ));
+        assert!(Game::new(GameConfig::standard(2).unwrap(), red_ones(10)).is_ok());
+    }
+
+    #[test]
+    fn test_undo() {
+        use crate::agent::Agent;
+        use crate::bots::{RandomBot, SafeBot};
+        use crate::rules::generate_normal_deck;
+
+        // SafeBot runs the deck out, RandomBot strikes out
+        let agents: Vec<Box<dyn Agent>> =
+            vec![Box::new(SafeBot::new()), Box::new(RandomBot::new(3))];
+        for mut agent in agents {
+            let mut game =
+                Game::from_seed(GameConfig::standard(3).unwrap(), generate_normal_deck(), 9)
+                    .unwrap();
+            let mut snapshots = vec![game.spectator_view(true)];
+
+            while let GameResultState::InProgress = game.finished() {
+                let uid = game.uid_of(game.active_player()).unwrap();
+                let mv = agent.choose_move(&game.view_for(uid).unwrap());
+                game.play_move(mv, uid).unwrap();
+                snapshots.push(game.spectator_view(true));
+            }
+
+            // Can't undo more moves than were made
+            let num_moves = game.log().len();
+            assert!(matches!(
+                game.undo(num_moves + 1),
+                Err(HanabiError::OutOfBounds { .. })
+            ));
+            assert_eq!(&game.spectator_view(true), snapshots.last().unwrap());
+
+            // One at a time, every earlier position comes back exactly
+            let last = game.log().last().cloned().unwrap();
+            assert_eq!(game.undo(1).unwrap(), vec![last]);
+            assert_eq!(game.spectator_view(true), snapshots[num_moves - 1]);
+
+            // Several at once, most recent first
+            let expected: Vec<Action> = game.log()[num_moves - 4..].iter().rev().cloned().collect();
+            assert_eq!(game.undo(3).unwrap(), expected);
+            assert_eq!(game.spectator_view(true), snapshots[num_moves - 4]);
+
+            while !game.log().is_empty() {
+                game.undo(1).unwrap();
+                assert_eq!(game.spectator_view(true), snapshots[game.log().len()]);
+            }
+            assert!(game.undo(0).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_rainbow_variant() {
+        use crate::card::{Color, Number};
+        use crate::rules::Variant;
+
+        let mut config = GameConfig::standard(2).unwrap();
+        config.variant = Variant::Rainbow;
+        assert_eq!(config.deck_size(), 60);
+        assert_eq!(config.max_score(), 30);
+        assert_eq!(config.generate_deck().len(), 60);
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let m2 = Card::new(Color::Rainbow, Number::Two);
+        let b3 = Card::new(Color::Blue, Number::Three);
+        let w4 = Card::new(Color::White, Number::Four);
+
+        // Rainbow cards don't exist in the standard game
+        assert!(matches!(
+            GameBuilder::new(GameConfig::standard(2).unwrap(), VecDeque::new())
+                .hands(vec![vec![w4], vec![m2]])
+                .build(),
+            Err(HanabiError::TooManyCopies(_))
+        ));
+
+        let deck: VecDeque<Card> = vec![Card::new(Color::Green, Number::Five)].into();
+        let mut game = GameBuilder::new(config, deck)
+            .hands(vec![vec![w4], vec![r1, m2, b3]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        // Every color clue reaches the rainbow card, but rainbow itself can't be named
+        let moves = game.legal_moves(uids[0]).unwrap();
+        for color in &[
+            Color::Red,
+            Color::White,
+            Color::Blue,
+            Color::Green,
+            Color::Yellow,
+        ] {
+            assert!(moves.contains(&HanabiMove::Hint((1, Hint::ColorHint(*color)))));
+        }
+        assert_eq!(game.view_for(uids[0]).unwrap().legal_moves(), moves);
+        assert!(matches!(
+            game.play_move(
+                HanabiMove::Hint((1, Hint::ColorHint(Color::Rainbow))),
+                uids[0]
+            ),
+            Err(HanabiError::NotAClueColor(Color::Rainbow))
+        ));
+
+        // A fresh card could be any of the six suits
+        assert_eq!(
+            game.get_player_knowledge(1).unwrap()[0].possible_colors(),
+            Variant::Rainbow.suits().to_vec()
+        );
+
+        let action = game
+            .play_move(HanabiMove::Hint((1, Hint::ColorHint(Color::Red))), uids[0])
+            .unwrap();
+        assert_eq!(
+            action.kind,
+            ActionKind::Hint {
+                target: 1,
+                hint: Hint::ColorHint(Color::Red),
+                touched: vec![0, 1],
+            }
+        );
+
+        let knowledge = game.get_player_knowledge(1).unwrap();
+        assert_eq!(
+            knowledge[0].possible_colors(),
+            vec![Color::Red, Color::Rainbow]
+        );
+        assert_eq!(
+            knowledge[1].possible_colors(),
+            vec![Color::Red, Color::Rainbow]
+        );
+        assert_eq!(
+            knowledge[2].possible_colors(),
+            vec![Color::White, Color::Blue, Color::Green, Color::Yellow]
+        );
+
+        // A blue clue touches the rainbow card too, which pins it down
+        game.play_move(
+            HanabiMove::Hint((0, Hint::ColorHint(Color::White))),
+            uids[1],
+        )
+        .unwrap();
+        game.play_move(HanabiMove::Hint((1, Hint::ColorHint(Color::Blue))), uids[0])
+            .unwrap();
+        let knowledge = game.get_player_knowledge(1).unwrap();
+        assert_eq!(knowledge[0].possible_colors(), vec![Color::Red]);
+        assert_eq!(knowledge[1].possible_colors(), vec![Color::Rainbow]);
+        assert_eq!(knowledge[2].possible_colors(), vec![Color::Blue]);
+    }
+
+    #[test]
+    fn test_black_variant() {
+        use crate::card::{Color, Number, NUMBERS};
+        use crate::rules::Variant;
+
+        let mut config = GameConfig::standard(2).unwrap();
+        config.variant = Variant::Black;
+        assert_eq!(config.deck_size(), 55);
+        assert_eq!(config.max_score(), 30);
+        assert_eq!(config.suit_max_score(Color::Black), 5);
+
+        // Exactly one of each black card, whatever the other suits get
+        let deck = config.generate_deck();
+        assert_eq!(deck.len(), 55);
+        for &number in &NUMBERS {
+            let k = Card::new(Color::Black, number);
+            assert_eq!(deck.iter().filter(|&&c| c == k).count(), 1);
+        }
+        config.card_frequencies = [1, 0, 0, 0, 0];
+        assert_eq!(config.deck_size(), 10);
+        assert_eq!(config.max_score(), 10);
+        config.card_frequencies = GameConfig::standard(2).unwrap().card_frequencies;
+
+        let r1 = Card::new(Color::Red, Number::One);
+        let k1 = Card::new(Color::Black, Number::One);
+        let k2 = Card::new(Color::Black, Number::Two);
+        let b3 = Card::new(Color::Blue, Number::Three);
+
+        // A second copy of a black card is one too many
+        assert!(matches!(
+            GameBuilder::new(config.clone(), VecDeque::new())
+                .hands(vec![vec![k1], vec![k1]])
+                .build(),
+            Err(HanabiError::TooManyCopies(_))
+        ));
+
+        let deck: VecDeque<Card> = vec![Card::new(Color::Green, Number::Five)].into();
+        let mut game = GameBuilder::new(config, deck)
+            .hands(vec![vec![b3], vec![r1, k1, k2]])
+            .build()
+            .unwrap();
+        let uids: Vec<UID> = game.players.iter().map(|p| p.uid).collect();
+
+        // Black can be named, and only a black clue touches black cards
+        let action = game
+            .play_move(HanabiMove::Hint((1, Hint::ColorHint(Color::Red))), uids[0])
+            .unwrap();
+        assert!(matches!(action.kind, ActionKind::Hint { ref touched, .. } if touched == &[0]));
+        game.play_move(HanabiMove::Hint((0, Hint::ColorHint(Color::Blue))), uids[1])
+            .unwrap();
+        let action = game
+            .play_move(
+                HanabiMove::Hint((1, Hint::ColorHint(Color::Black))),
+                uids[0],
+            )
+            .unwrap();
+        assert!(matches!(action.kind, ActionKind::Hint { ref touched, .. } if touched == &[1, 2]));
+
+        let knowledge = game.get_player_knowledge(1).unwrap();
+        assert_eq!(knowledge[0].possible_colors(), vec![Color::Red]);
+        assert_eq!(knowledge[1].possible_colors(), vec![Color::Black]);
+        assert_eq!(knowledge[2].possible_colors(), vec![Color::Black]);
+    }
 }
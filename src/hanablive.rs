@@ -0,0 +1,525 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{Card, Color, Number, NUMBERS};
+use crate::errors::HanabiError;
+use crate::game::{Game, GameBuilder};
+use crate::moves::{ActionKind, HanabiMove, Hint};
+use crate::player::PubID;
+use crate::replay::Replay;
+use crate::rules::{GameConfig, Variant};
+
+// hanab.live action types
+const PLAY: u8 = 0;
+const DISCARD: u8 = 1;
+const COLOR_CLUE: u8 = 2;
+const RANK_CLUE: u8 = 3;
+const GAME_OVER: u8 = 4;
+
+const NO_VARIANT: &str = "No Variant";
+const RAINBOW: &str = "Rainbow (6 Suits)";
+const BLACK: &str = "Black (6 Suits)";
+
+/**
+ * @brief hanab.live's suit order. It calls our White suit Purple, and 6 suit variants put their
+ * extra suit last.
+ */
+const SUITS: [Color; 5] = [
+    Color::Red,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::White,
+];
+const RAINBOW_SUITS: [Color; 6] = [
+    Color::Red,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::White,
+    Color::Rainbow,
+];
+const BLACK_SUITS: [Color; 6] = [
+    Color::Red,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::White,
+    Color::Black,
+];
+
+fn suits(variant: Variant) -> &'static [Color] {
+    match variant {
+        Variant::Standard => &SUITS,
+        Variant::Rainbow => &RAINBOW_SUITS,
+        Variant::Black => &BLACK_SUITS,
+    }
+}
+
+fn parse_variant(name: &str) -> Result<Variant, HanabiError> {
+    match name {
+        NO_VARIANT => Ok(Variant::Standard),
+        RAINBOW => Ok(Variant::Rainbow),
+        BLACK => Ok(Variant::Black),
+        _ => Err(HanabiError::HanabLiveVariant(name.to_string())),
+    }
+}
+
+fn variant_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Standard => NO_VARIANT,
+        Variant::Rainbow => RAINBOW,
+        Variant::Black => BLACK,
+    }
+}
+
+/**
+ * @brief A game in hanab.live's JSON export format, which community tools read and write.
+ * Serialize and deserialize it with any serde format (normally serde_json).
+ *
+ * Cards are identified by their "order": their index in the deck, with the opening hands dealt
+ * one whole player at a time.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HanabLiveGame {
+    pub players: Vec<String>,
+    pub deck: Vec<HanabLiveCard>,
+    pub actions: Vec<HanabLiveAction>,
+    #[serde(default)]
+    pub options: HanabLiveOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HanabLiveCard {
+    pub suit_index: usize,
+    pub rank: usize,
+}
+
+/**
+ * @brief For plays and discards target is the card's order, for clues it is the player clued
+ * and value is the suit index or rank.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HanabLiveAction {
+    #[serde(rename = "type")]
+    pub kind: u8,
+    pub target: usize,
+    #[serde(default)]
+    pub value: usize,
+}
+
+/**
+ * @brief The game options this library understands. Anything else hanab.live writes here is
+ * ignored, so options that change how the actions replay must be listed and checked.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HanabLiveOptions {
+    #[serde(default = "no_variant")]
+    pub variant: String,
+    // Our games always start with player 0
+    #[serde(default)]
+    pub starting_player: usize,
+}
+
+impl Default for HanabLiveOptions {
+    fn default() -> Self {
+        HanabLiveOptions {
+            variant: no_variant(),
+            starting_player: 0,
+        }
+    }
+}
+
+fn no_variant() -> String {
+    NO_VARIANT.to_string()
+}
+
+impl HanabLiveCard {
+    pub fn to_card(self, variant: Variant) -> Result<Card, HanabiError> {
+        match (suits(variant).get(self.suit_index), self.rank) {
+            (Some(&color), 1..=5) => Ok(Card::new(color, NUMBERS[self.rank - 1])),
+            _ => Err(HanabiError::HanabLiveCard {
+                suit_index: self.suit_index,
+                rank: self.rank,
+            }),
+        }
+    }
+
+    pub fn from_card(card: Card, variant: Variant) -> Self {
+        HanabLiveCard {
+            suit_index: suit_index(card.color(), variant),
+            rank: rank(card.number()),
+        }
+    }
+}
+
+fn suit_index(color: Color, variant: Variant) -> usize {
+    suits(variant).iter().position(|&c| c == color).unwrap()
+}
+
+fn rank(number: Number) -> usize {
+    NUMBERS.iter().position(|&n| n == number).unwrap() + 1
+}
+
+/**
+ * @brief Which card order sits in each slot of each hand, kept in step with the Game's hands
+ */
+struct CardOrders {
+    hands: Vec<Vec<usize>>,
+    next: usize,
+    deck_len: usize,
+}
+
+impl CardOrders {
+    fn deal(num_players: usize, hand_size: usize, deck_len: usize) -> Self {
+        let hands = (0..num_players)
+            .map(|p| (p * hand_size..(p + 1) * hand_size).collect())
+            .collect();
+
+        CardOrders {
+            hands,
+            next: num_players * hand_size,
+            deck_len,
+        }
+    }
+
+    /**
+     * @brief Take the card out of the player's hand and draw a replacement, if any are left
+     */
+    fn remove(&mut self, player: usize, slot: usize) -> usize {
+        let order = self.hands[player].remove(slot);
+        if self.next < self.deck_len {
+            self.hands[player].push(self.next);
+            self.next += 1;
+        }
+        order
+    }
+}
+
+impl HanabLiveGame {
+    /**
+     * @brief Set the game up from the exported deck and replay its actions
+     */
+    pub fn to_replay(&self) -> Result<Replay, HanabiError> {
+        let mut config = GameConfig::standard(self.players.len())?;
+        config.variant = parse_variant(&self.options.variant)?;
+        if self.options.starting_player != 0 {
+            return Err(HanabiError::HanabLiveStartingPlayer(
+                self.options.starting_player,
+            ));
+        }
+        let num_players = config.num_players;
+        let hand_size = config.hand_size;
+
+        let mut deck = self
+            .deck
+            .iter()
+            .map(|card| card.to_card(config.variant))
+            .collect::<Result<VecDeque<Card>, HanabiError>>()?;
+        if deck.len() < num_players * hand_size {
+            return Err(HanabiError::DeckTooSmall {
+                deck_len: deck.len(),
+                needed: num_players * hand_size,
+            });
+        }
+        let hands: Vec<Vec<Card>> = (0..num_players)
+            .map(|_| deck.drain(..hand_size).collect())
+            .collect();
+
+        let mut orders = CardOrders::deal(num_players, hand_size, self.deck.len());
+        let mut moves = Vec::new();
+        for (turn, action) in self.actions.iter().enumerate() {
+            let active = turn % num_players;
+            let invalid = || HanabiError::HanabLiveAction(turn);
+            let clued = || {
+                if action.target < num_players {
+                    Ok(action.target as PubID)
+                } else {
+                    Err(invalid())
+                }
+            };
+
+            let mv = match action.kind {
+                PLAY | DISCARD => {
+                    let slot = orders.hands[active]
+                        .iter()
+                        .position(|&order| order == action.target)
+                        .ok_or_else(invalid)?;
+                    orders.remove(active, slot);
+
+                    if action.kind == PLAY {
+                        HanabiMove::Play(slot)
+                    } else {
+                        HanabiMove::Discard(slot)
+                    }
+                }
+                COLOR_CLUE => {
+                    let color = config
+                        .variant
+                        .clue_colors()
+                        .iter()
+                        .find(|&&color| suit_index(color, config.variant) == action.value)
+                        .ok_or_else(invalid)?;
+                    HanabiMove::Hint((clued()?, Hint::ColorHint(*color)))
+                }
+                RANK_CLUE => {
+                    let number = match action.value {
+                        1..=5 => NUMBERS[action.value - 1],
+                        _ => return Err(invalid()),
+                    };
+                    HanabiMove::Hint((clued()?, Hint::NumberHint(number)))
+                }
+                GAME_OVER => break,
+                _ => return Err(invalid()),
+            };
+            moves.push(mv);
+        }
+
+        let game = GameBuilder::new(config, deck)
+            .hands(hands)
+            .seed(0)
+            .build()?;
+        Replay::new(game, moves)
+    }
+
+    /**
+     * @brief Export a game, with every move made so far. The game must have started from a
+     * normal deal: full hands, nothing on the board and every hint available.
+     */
+    pub fn from_game(game: &Game) -> Result<Self, HanabiError> {
+        let config = game.config();
+        let num_moves = game.log().len();
+
+        let mut start = game.clone();
+        start.undo(num_moves)?;
+        let start = start.spectator_view(true);
+
+        let fresh = start.board.is_empty()
+            && start.discard.is_empty()
+            && start.hints == config.max_hints
+            && start.strikes == 0
+            && start.active_player == 0
+            && start.turns_since_last_pickup.unwrap_or(0) == 0
+            && start
+                .hands
+                .iter()
+                .all(|h| h.cards.len() == config.hand_size);
+        if !fresh {
+            return Err(HanabiError::HanabLiveNotFromStart);
+        }
+
+        let deck: Vec<HanabLiveCard> = start
+            .hands
+            .iter()
+            .flat_map(|hand| hand.cards.iter().map(|(card, _)| *card))
+            .chain(start.deck.unwrap_or_default())
+            .map(|card| HanabLiveCard::from_card(card, config.variant))
+            .collect();
+
+        let mut orders = CardOrders::deal(config.num_players, config.hand_size, deck.len());
+        let actions = game
+            .log()
+            .iter()
+            .map(|action| {
+                let player = action.player as usize;
+                match action.kind {
+                    ActionKind::Play { slot, .. } => HanabLiveAction {
+                        kind: PLAY,
+                        target: orders.remove(player, slot),
+                        value: 0,
+                    },
+                    ActionKind::Discard { slot, .. } => HanabLiveAction {
+                        kind: DISCARD,
+                        target: orders.remove(player, slot),
+                        value: 0,
+                    },
+                    ActionKind::Hint {
+                        target,
+                        hint: Hint::ColorHint(color),
+                        ..
+                    } => HanabLiveAction {
+                        kind: COLOR_CLUE,
+                        target: target as usize,
+                        value: suit_index(color, config.variant),
+                    },
+                    ActionKind::Hint {
+                        target,
+                        hint: Hint::NumberHint(number),
+                        ..
+                    } => HanabLiveAction {
+                        kind: RANK_CLUE,
+                        target: target as usize,
+                        value: rank(number),
+                    },
+                }
+            })
+            .collect();
+
+        Ok(HanabLiveGame {
+            players: (0..config.num_players)
+                .map(|p| format!("Player {}", p))
+                .collect(),
+            deck,
+            actions,
+            options: HanabLiveOptions {
+                variant: variant_name(config.variant).to_string(),
+                starting_player: 0,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::SafeBot;
+    use crate::rules::GameResultState;
+    use crate::sim::play_seed;
+
+    #[test]
+    fn test_import() {
+        // Two players dealt r1-r5 and y1-y5. Alice clues Bob's 1 and Bob plays it, drawing g1.
+        // Alice discards her r5, drawing the last card, and Bob clues her about it: purple
+        let json = r#"{
+            "players": ["Alice", "Bob"],
+            "deck": [
+                {"suitIndex": 0, "rank": 1}, {"suitIndex": 0, "rank": 2},
+                {"suitIndex": 0, "rank": 3}, {"suitIndex": 0, "rank": 4},
+                {"suitIndex": 0, "rank": 5},
+                {"suitIndex": 1, "rank": 1}, {"suitIndex": 1, "rank": 2},
+                {"suitIndex": 1, "rank": 3}, {"suitIndex": 1, "rank": 4},
+                {"suitIndex": 1, "rank": 5},
+                {"suitIndex": 2, "rank": 1}, {"suitIndex": 4, "rank": 1}
+            ],
+            "actions": [
+                {"type": 3, "target": 1, "value": 1},
+                {"type": 0, "target": 5, "value": 0},
+                {"type": 1, "target": 4, "value": 0},
+                {"type": 2, "target": 0, "value": 4},
+                {"type": 4, "target": 0, "value": 4}
+            ],
+            "options": {"variant": "No Variant", "speedrun": false}
+        }"#;
+
+        let imported: HanabLiveGame = serde_json::from_str(json).unwrap();
+        let mut replay = imported.to_replay().unwrap();
+        assert_eq!(replay.len(), 4);
+        assert_eq!(
+            replay.moves(),
+            &[
+                HanabiMove::Hint((1, Hint::NumberHint(Number::One))),
+                HanabiMove::Play(0),
+                HanabiMove::Discard(4),
+                HanabiMove::Hint((0, Hint::ColorHint(Color::White))),
+            ]
+        );
+
+        replay.seek(4).unwrap();
+        let view = replay.game().spectator_view(true);
+        assert_eq!(view.board, vec![Card::new(Color::Yellow, Number::One)]);
+        assert_eq!(view.discard, vec![Card::new(Color::Red, Number::Five)]);
+        assert_eq!(
+            view.hands[0].cards[4].0,
+            Card::new(Color::White, Number::One)
+        );
+        assert_eq!(
+            view.hands[1].cards[4].0,
+            Card::new(Color::Green, Number::One)
+        );
+        assert_eq!(view.deck, Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let mut game = HanabLiveGame {
+            players: vec!["Alice".to_string(), "Bob".to_string()],
+            deck: (0..10)
+                .map(|i| HanabLiveCard {
+                    suit_index: i / 5,
+                    rank: i % 5 + 1,
+                })
+                .collect(),
+            actions: vec![HanabLiveAction {
+                kind: PLAY,
+                target: 5,
+                value: 0,
+            }],
+            options: HanabLiveOptions::default(),
+        };
+
+        // Card 5 is in Bob's hand, not Alice's
+        assert!(matches!(
+            game.to_replay(),
+            Err(HanabiError::HanabLiveAction(0))
+        ));
+
+        game.actions.clear();
+        game.deck[3].rank = 7;
+        assert!(matches!(
+            game.to_replay(),
+            Err(HanabiError::HanabLiveCard {
+                suit_index: 0,
+                rank: 7
+            })
+        ));
+
+        game.options.variant = "Pink (6 Suits)".to_string();
+        assert!(matches!(
+            game.to_replay(),
+            Err(HanabiError::HanabLiveVariant(_))
+        ));
+
+        // Every action would be credited to the wrong player
+        let options: HanabLiveOptions =
+            serde_json::from_str(r#"{"variant": "No Variant", "startingPlayer": 1}"#).unwrap();
+        game.options = options;
+        assert!(matches!(
+            game.to_replay(),
+            Err(HanabiError::HanabLiveStartingPlayer(1))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &(variant, deck_len) in &[
+            (Variant::Standard, 50),
+            (Variant::Rainbow, 60),
+            (Variant::Black, 55),
+        ] {
+            round_trip(variant, deck_len);
+        }
+    }
+
+    fn round_trip(variant: Variant, deck_len: usize) {
+        let mut config = GameConfig::standard(4).unwrap();
+        config.variant = variant;
+        let mut agents: Vec<Box<SafeBot>> = (0..4).map(|_| Box::new(SafeBot::new())).collect();
+        let record = play_seed(&config, 11, &mut agents).unwrap();
+
+        let mut game = Game::from_seed(config.clone(), config.generate_deck(), 11).unwrap();
+        for action in &record.log {
+            let uid = game.uid_of(game.active_player()).unwrap();
+            game.play_move(action.as_move(), uid).unwrap();
+        }
+
+        let exported = HanabLiveGame::from_game(&game).unwrap();
+        assert_eq!(exported.options.variant, variant_name(variant));
+        assert_eq!(exported.deck.len(), deck_len);
+        assert_eq!(exported.actions.len(), record.log.len());
+
+        let json = serde_json::to_string(&exported).unwrap();
+        let imported: HanabLiveGame = serde_json::from_str(&json).unwrap();
+        assert_eq!(imported, exported);
+
+        let mut replay = imported.to_replay().unwrap();
+        assert_eq!(
+            replay.result(),
+            GameResultState::Finished(record.reason, record.score)
+        );
+        replay.seek(replay.len()).unwrap();
+        assert_eq!(replay.game().log(), &record.log[..]);
+    }
+}
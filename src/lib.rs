@@ -1,9 +1,8 @@
 #![allow(dead_code)]
 
 // TODO:
-//      1. get rid of to_string() for errors?
-//      2. Make Player own the cards?
-//      3.
+//      1. Make Player own the cards?
+//      2.
 
 #[macro_use]
 extern crate bitflags;
@@ -16,6 +15,13 @@ pub mod card;
 pub mod errors;
 pub mod rules;
 
+pub mod agent;
+pub mod bots;
 pub mod game;
+#[cfg(feature = "serde")]
+pub mod hanablive;
 pub mod moves;
 pub mod player;
+pub mod replay;
+pub mod sim;
+pub mod view;
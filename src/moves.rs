@@ -1,17 +1,233 @@
-use crate::card::{Color, Number};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::card::{Card, CardView, Color, Number};
+use crate::errors::HanabiError;
 use crate::player::PubID;
+use crate::rules::Variant;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Hint {
     ColorHint(Color),
     NumberHint(Number),
 }
 
+// A hint is written as just the color ("red") or number ("4") it reveals
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hint::ColorHint(color) => color.fmt(f),
+            Hint::NumberHint(number) => number.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Hint {
+    type Err = HanabiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Hint::NumberHint)
+            .or_else(|_| s.parse().map(Hint::ColorHint))
+            .map_err(|_| HanabiError::InvalidHint(s.to_string()))
+    }
+}
+
 pub type HintForPlayer = (PubID, Hint);
 
-#[derive(Debug)]
+/**
+ * @brief Every distinct hint that touches at least one of the given cards: colors first, then
+ * numbers, each in the order they first appear in the hand
+ */
+pub fn hints_for(target: PubID, cards: &[CardView], variant: Variant) -> Vec<HanabiMove> {
+    let every_hint = cards
+        .iter()
+        .flat_map(|c| {
+            variant
+                .clue_colors()
+                .iter()
+                .filter(move |&&clue| variant.color_touches(clue, c.color()))
+                .map(|&clue| Hint::ColorHint(clue))
+        })
+        .chain(cards.iter().map(|c| Hint::NumberHint(c.number())));
+
+    let mut hints: Vec<Hint> = Vec::new();
+    for hint in every_hint {
+        if !hints.contains(&hint) {
+            hints.push(hint);
+        }
+    }
+
+    hints
+        .into_iter()
+        .map(|hint| HanabiMove::Hint((target, hint)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum HanabiMove {
     Play(usize),    // usize is index in hand of which card to play
     Discard(usize), // usize is index in hand of which card to discard
     Hint(HintForPlayer),
 }
+
+// Moves are written "play <slot>", "discard <slot>" or "clue <player> <hint>"
+impl fmt::Display for HanabiMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HanabiMove::Play(idx) => write!(f, "play {}", idx),
+            HanabiMove::Discard(idx) => write!(f, "discard {}", idx),
+            HanabiMove::Hint((pub_id, hint)) => write!(f, "clue {} {}", pub_id, hint),
+        }
+    }
+}
+
+impl FromStr for HanabiMove {
+    type Err = HanabiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || HanabiError::InvalidMoveNotation(s.to_string());
+
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words.as_slice() {
+            ["play", idx] => Ok(HanabiMove::Play(idx.parse().map_err(|_| invalid())?)),
+            ["discard", idx] => Ok(HanabiMove::Discard(idx.parse().map_err(|_| invalid())?)),
+            ["clue", pub_id, hint] => Ok(HanabiMove::Hint((
+                pub_id.parse().map_err(|_| invalid())?,
+                hint.parse()?,
+            ))),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/**
+ * @brief One entry in a game's action log: who did what, and what it revealed. It includes the
+ * card that was drawn, so before showing an action to a player pass it through seen_by(viewer).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Action {
+    pub player: PubID,
+    pub kind: ActionKind,
+}
+
+impl Action {
+    /**
+     * @brief The action as the given player is allowed to see it: players never get to see the
+     * card they drew themselves.
+     */
+    pub fn seen_by(&self, viewer: PubID) -> Action {
+        let mut action = self.clone();
+        if viewer == self.player {
+            match &mut action.kind {
+                ActionKind::Play { drawn, .. } | ActionKind::Discard { drawn, .. } => *drawn = None,
+                ActionKind::Hint { .. } => {}
+            }
+        }
+        action
+    }
+
+    /**
+     * @brief The card that was drawn after this action, if it is visible
+     */
+    pub fn drawn(&self) -> Option<Card> {
+        match self.kind {
+            ActionKind::Play { drawn, .. } | ActionKind::Discard { drawn, .. } => drawn,
+            ActionKind::Hint { .. } => None,
+        }
+    }
+
+    /**
+     * @brief The move that produced this action
+     */
+    pub fn as_move(&self) -> HanabiMove {
+        match self.kind {
+            ActionKind::Play { slot, .. } => HanabiMove::Play(slot),
+            ActionKind::Discard { slot, .. } => HanabiMove::Discard(slot),
+            ActionKind::Hint { target, hint, .. } => HanabiMove::Hint((target, hint)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActionKind {
+    Play {
+        slot: usize,
+        card: Card,
+        success: bool,
+        drawn: Option<Card>, // None once the deck has run out, or when hidden from the viewer
+    },
+    Discard {
+        slot: usize,
+        card: Card,
+        regained_hint: bool,
+        drawn: Option<Card>, // None once the deck has run out, or when hidden from the viewer
+    },
+    Hint {
+        target: PubID,
+        hint: Hint,
+        touched: Vec<usize>, // slots in the target's hand that the hint touched
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_notation() {
+        let moves = vec![
+            ("play 2", HanabiMove::Play(2)),
+            ("discard 0", HanabiMove::Discard(0)),
+            (
+                "clue 3 red",
+                HanabiMove::Hint((3, Hint::ColorHint(Color::Red))),
+            ),
+            (
+                "clue 1 4",
+                HanabiMove::Hint((1, Hint::NumberHint(Number::Four))),
+            ),
+        ];
+
+        for (text, mv) in moves {
+            assert_eq!(text.parse::<HanabiMove>().unwrap(), mv);
+            assert_eq!(mv.to_string(), text);
+        }
+
+        // Extra whitespace and color letters are fine too
+        assert_eq!(
+            "  clue 0   g ".parse::<HanabiMove>().unwrap(),
+            HanabiMove::Hint((0, Hint::ColorHint(Color::Green)))
+        );
+    }
+
+    #[test]
+    fn test_bad_move_notation() {
+        for bad in &[
+            "",
+            "play",
+            "play -1",
+            "play x",
+            "discard 1 2",
+            "fly 1",
+            "clue red",
+        ] {
+            assert!(matches!(
+                bad.parse::<HanabiMove>(),
+                Err(HanabiError::InvalidMoveNotation(ref s)) if s == bad
+            ));
+        }
+
+        assert!(matches!(
+            "clue 1 purple".parse::<HanabiMove>(),
+            Err(HanabiError::InvalidHint(ref s)) if s == "purple"
+        ));
+    }
+}
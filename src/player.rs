@@ -1,15 +1,20 @@
 //use std::fmt::Debug;
 use crate::card::{
-    not_this_color, not_this_number, this_color, this_number, Card, CardKnowledge, CardView, Color,
-    Number,
+    none_of_colors, not_this_number, one_of_colors, this_number, Card, CardKnowledge, CardView,
+    Color, ColorKnowledge, Number,
 };
 use crate::moves::Hint;
-use crate::rules::{MAX_PLAYERS, MIN_PLAYERS};
+use crate::rules::{Variant, MIN_PLAYERS};
 
 use crate::errors::HanabiError;
 
-fn generate_uid() -> UID {
-    rand::random::<u64>()
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+fn generate_uid<R: Rng + ?Sized>(rng: &mut R) -> UID {
+    rng.gen::<u64>()
 }
 
 //#[derive(Debug)]
@@ -18,7 +23,8 @@ pub type UID = u64;
 //#[derive(Debug)]
 pub type PubID = u8;
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Player {
     pub public_id: PubID,
     pub uid: UID,
@@ -36,10 +42,21 @@ impl Player {
         }
     }
 
+    /**
+     * @brief Add a card to the end of the hand, known only to be one of the standard five suits.
+     * Variants with other suits use push_card_with_knowledge.
+     */
     pub fn push_card(&mut self, card: Card) {
         self.hand.push((card, CardKnowledge::new()))
     }
 
+    /**
+     * @brief Add a card to the end of the hand along with what the player already knows about it
+     */
+    pub fn push_card_with_knowledge(&mut self, card: Card, knowledge: CardKnowledge) {
+        self.hand.push((card, knowledge))
+    }
+
     // TODO: re-implement Index trait?
     pub fn hand_at(&self, idx: usize) -> &(Card, CardKnowledge) {
         &self.hand[idx]
@@ -49,6 +66,20 @@ impl Player {
         self.hand.remove(idx)
     }
 
+    /**
+     * @brief Put a card back into the hand at the given slot, e.g. when undoing a move
+     */
+    pub fn insert_card(&mut self, idx: usize, card: Card, knowledge: CardKnowledge) {
+        self.hand.insert(idx, (card, knowledge))
+    }
+
+    /**
+     * @brief Take back the most recently drawn card
+     */
+    pub fn pop_card(&mut self) -> Option<(Card, CardKnowledge)> {
+        self.hand.pop()
+    }
+
     pub fn hand_view(&self) -> Vec<CardView> {
         self.hand.iter().map(|(card, _)| card.view()).collect()
     }
@@ -65,6 +96,12 @@ impl Player {
         self.hand.iter().any(|(x, _)| x.number() == number)
     }
 
+    pub fn any_touched_by(&self, hint: Hint, variant: Variant) -> bool {
+        self.hand
+            .iter()
+            .any(|(x, _)| variant.hint_touches(hint, *x))
+    }
+
     pub fn get_knowledge(&self) -> Vec<CardKnowledge> {
         self.hand
             .iter()
@@ -72,20 +109,45 @@ impl Player {
             .collect()
     }
 
-    pub fn give_hint(&mut self, hint: Hint) -> Result<(), HanabiError> {
+    /**
+     * @brief Overwrite what the player knows about each card in their hand, slot 0 first
+     */
+    pub fn set_knowledge(&mut self, knowledge: Vec<CardKnowledge>) {
+        for ((_, card_knowledge), new_knowledge) in self.hand.iter_mut().zip(knowledge) {
+            *card_knowledge = new_knowledge;
+        }
+    }
+
+    /**
+     * @brief Update the knowledge of every card in the hand with the given hint
+     *
+     * @return The slots of the cards that the hint touched
+     */
+    pub fn give_hint(&mut self, hint: Hint, variant: Variant) -> Result<Vec<usize>, HanabiError> {
+        let mut touched = Vec::new();
+
         match hint {
             Hint::ColorHint(color) => {
-                for (card, card_knowledge) in &mut self.hand {
-                    if card.color() == color {
-                        *card_knowledge = this_color(card_knowledge.clone(), color)?;
+                // Every suit this clue touches, e.g. red and rainbow
+                let colors = variant
+                    .suits()
+                    .iter()
+                    .filter(|&&suit| variant.color_touches(color, suit))
+                    .fold(ColorKnowledge::empty(), |acc, &suit| acc | suit.into());
+
+                for (idx, (card, card_knowledge)) in self.hand.iter_mut().enumerate() {
+                    if variant.color_touches(color, card.color()) {
+                        touched.push(idx);
+                        *card_knowledge = one_of_colors(card_knowledge.clone(), color, colors)?;
                     } else {
-                        *card_knowledge = not_this_color(card_knowledge.clone(), color)?;
+                        *card_knowledge = none_of_colors(card_knowledge.clone(), colors)?;
                     }
                 }
             }
             Hint::NumberHint(number) => {
-                for (card, card_knowledge) in &mut self.hand {
+                for (idx, (card, card_knowledge)) in self.hand.iter_mut().enumerate() {
                     if card.number() == number {
+                        touched.push(idx);
                         *card_knowledge = this_number(card_knowledge.clone(), number)?;
                     } else {
                         *card_knowledge = not_this_number(card_knowledge.clone(), number)?;
@@ -94,7 +156,7 @@ impl Player {
             }
         }
 
-        Ok(())
+        Ok(touched)
     }
 }
 
@@ -103,29 +165,28 @@ pub fn get_public_id(players: &[Player], uid: UID) -> Result<PubID, HanabiError>
         .iter()
         .find(|p| p.uid == uid)
         .map(|p| p.public_id)
-        .ok_or_else(|| HanabiError::InvalidMove("That uid doesn't exist".to_string()))
+        .ok_or(HanabiError::UnknownPlayer(uid))
 }
 
-// TODO: might be able to remove this
 pub fn get_id(players: &[Player], pub_id: PubID) -> Result<UID, HanabiError> {
     players
         .iter()
         .find(|p| p.public_id == pub_id)
         .map(|p| p.uid)
-        .ok_or_else(|| HanabiError::InvalidMove("That PubID doesn't exist".to_string()))
+        .ok_or(HanabiError::UnknownPublicId(pub_id))
 }
 
 /**
- * @brief Create players, initialized with IDs
+ * @brief Create players, initialized with IDs drawn from the given RNG
  */
-pub fn generate_players(num_players: usize) -> Vec<Player> {
-    assert!(num_players as u8 <= MAX_PLAYERS && num_players as u8 >= MIN_PLAYERS);
+pub fn generate_players<R: Rng + ?Sized>(num_players: usize, rng: &mut R) -> Vec<Player> {
+    assert!(num_players <= PubID::MAX.into() && num_players >= MIN_PLAYERS.into());
 
     let mut players = Vec::new();
 
     // Create the players
     for public_id in 0..num_players {
-        let uid = generate_uid();
+        let uid = generate_uid(rng);
         let new_player = Player {
             public_id: public_id as u8,
             uid,
@@ -140,11 +201,12 @@ pub fn generate_players(num_players: usize) -> Vec<Player> {
 mod tests {
     use super::*;
     use crate::card::{CardKnowledge, ColorKnowledge, NumberKnowledge};
+    use rand::thread_rng;
 
     #[test]
     fn test_generate_players() {
         for num_players in 2..6 {
-            let players = generate_players(num_players);
+            let players = generate_players(num_players, &mut thread_rng());
             assert!(players.len() == num_players);
         }
     }
@@ -153,7 +215,7 @@ mod tests {
     fn test_give_hint() {
         let mut player = Player {
             public_id: 0,
-            uid: generate_uid(),
+            uid: generate_uid(&mut thread_rng()),
             hand: Vec::new(),
         };
 
@@ -166,7 +228,12 @@ mod tests {
         // ------------------
         // First hint
         // ------------------
-        player.give_hint(Hint::ColorHint(Color::Red)).unwrap();
+        // Missed by the red clue: not red, nor any suit the standard game doesn't have
+        let untouched = ColorKnowledge::RED | ColorKnowledge::RAINBOW | ColorKnowledge::BLACK;
+        let touched = player
+            .give_hint(Hint::ColorHint(Color::Red), Variant::Standard)
+            .unwrap();
+        assert_eq!(touched, vec![0, 1, 2]);
 
         let hand_knowledge: Vec<CardKnowledge> = (0..(player.hand_len()))
             .map(|idx| player.hand_at(idx))
@@ -187,11 +254,11 @@ mod tests {
                 not_these_numbers: NumberKnowledge::default(),
             },
             CardKnowledge {
-                not_these_colors: ColorKnowledge::RED,
+                not_these_colors: untouched,
                 not_these_numbers: NumberKnowledge::default(),
             },
             CardKnowledge {
-                not_these_colors: ColorKnowledge::RED,
+                not_these_colors: untouched,
                 not_these_numbers: NumberKnowledge::default(),
             },
         ];
@@ -200,7 +267,10 @@ mod tests {
         // ------------------
         // Second hint
         // ------------------
-        player.give_hint(Hint::NumberHint(Number::Two)).unwrap();
+        let touched = player
+            .give_hint(Hint::NumberHint(Number::Two), Variant::Standard)
+            .unwrap();
+        assert_eq!(touched, vec![2, 4]);
 
         let hand_knowledge: Vec<CardKnowledge> = (0..(player.hand_len()))
             .map(|idx| player.hand_at(idx))
@@ -221,11 +291,11 @@ mod tests {
                 not_these_numbers: NumberKnowledge::ALL_NUMBERS ^ NumberKnowledge::TWO,
             },
             CardKnowledge {
-                not_these_colors: ColorKnowledge::RED,
+                not_these_colors: untouched,
                 not_these_numbers: NumberKnowledge::TWO,
             },
             CardKnowledge {
-                not_these_colors: ColorKnowledge::RED,
+                not_these_colors: untouched,
                 not_these_numbers: NumberKnowledge::ALL_NUMBERS ^ NumberKnowledge::TWO,
             },
         ];
@@ -0,0 +1,240 @@
+use std::collections::VecDeque;
+
+use crate::card::Card;
+use crate::errors::{HanabiError, Limits};
+use crate::game::{Game, GameBuilder};
+use crate::moves::{Action, HanabiMove};
+use crate::rules::{GameConfig, GameResultState};
+
+/**
+ * @brief A recorded game that can be stepped through one turn at a time, in either direction.
+ *
+ * Every move is replayed (and checked for legality) up front, keeping a snapshot of the Game
+ * after each turn, so stepping around is free.
+ */
+pub struct Replay {
+    moves: Vec<HanabiMove>,
+    // states[i] is the game after the first i moves
+    states: Vec<Game>,
+    turn: usize,
+}
+
+impl Replay {
+    /**
+     * @brief Replay the moves starting from the given game, positioned at the start
+     */
+    pub fn new(game: Game, moves: Vec<HanabiMove>) -> Result<Self, HanabiError> {
+        let mut states = Vec::with_capacity(moves.len() + 1);
+        states.push(game);
+
+        for (turn, mv) in moves.iter().enumerate() {
+            let mut game = states[turn].clone();
+            game.uid_of(game.active_player())
+                .and_then(|uid| game.play_move(*mv, uid))
+                .map_err(|e| HanabiError::IllegalReplayMove {
+                    turn,
+                    source: Box::new(e),
+                })?;
+            states.push(game);
+        }
+
+        Ok(Replay {
+            moves,
+            states,
+            turn: 0,
+        })
+    }
+
+    /**
+     * @brief Replay a game dealt by Game::from_seed from the config's full deck
+     */
+    pub fn from_seed(
+        config: GameConfig,
+        seed: u64,
+        moves: Vec<HanabiMove>,
+    ) -> Result<Self, HanabiError> {
+        let deck = config.generate_deck();
+        Replay::new(Game::from_seed(config, deck, seed)?, moves)
+    }
+
+    /**
+     * @brief Replay a game dealt from a deck in a known order, front of the deck first
+     */
+    pub fn from_deck(
+        config: GameConfig,
+        deck: VecDeque<Card>,
+        moves: Vec<HanabiMove>,
+    ) -> Result<Self, HanabiError> {
+        Replay::new(GameBuilder::new(config, deck).seed(0).build()?, moves)
+    }
+
+    /**
+     * @brief The game as it stands at the current turn
+     */
+    pub fn game(&self) -> &Game {
+        &self.states[self.turn]
+    }
+
+    /**
+     * @brief How many moves have been made at the current position
+     */
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /**
+     * @brief Total number of moves in the replay
+     */
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn moves(&self) -> &[HanabiMove] {
+        &self.moves
+    }
+
+    /**
+     * @brief The action that led to the current position, if any
+     */
+    pub fn last_action(&self) -> Option<&Action> {
+        self.game().log().last()
+    }
+
+    /**
+     * @brief How the game stands after the final move
+     */
+    pub fn result(&self) -> GameResultState {
+        self.states[self.moves.len()].finished()
+    }
+
+    /**
+     * @brief Move one turn forward. Returns false if already at the end.
+     */
+    pub fn step_forward(&mut self) -> bool {
+        if self.turn == self.moves.len() {
+            return false;
+        }
+        self.turn += 1;
+        true
+    }
+
+    /**
+     * @brief Move one turn back. Returns false if already at the start.
+     */
+    pub fn step_back(&mut self) -> bool {
+        if self.turn == 0 {
+            return false;
+        }
+        self.turn -= 1;
+        true
+    }
+
+    /**
+     * @brief Jump to the position after the given number of moves
+     */
+    pub fn seek(&mut self, turn: usize) -> Result<(), HanabiError> {
+        if turn > self.moves.len() {
+            return Err(HanabiError::OutOfBounds {
+                idx: turn,
+                limits: Limits {
+                    lo: 0,
+                    hi: self.moves.len(),
+                },
+            });
+        }
+        self.turn = turn;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::SafeBot;
+    use crate::card::{Color, Number};
+    use crate::moves::Hint;
+    use crate::sim::play_seed;
+
+    #[test]
+    fn test_replay_from_seed() {
+        let config = GameConfig::standard(3).unwrap();
+        let mut agents: Vec<Box<SafeBot>> = (0..3).map(|_| Box::new(SafeBot::new())).collect();
+        let record = play_seed(&config, 42, &mut agents).unwrap();
+        let moves: Vec<HanabiMove> = record.log.iter().map(Action::as_move).collect();
+
+        let mut replay = Replay::from_seed(config.clone(), 42, moves).unwrap();
+        assert_eq!(replay.len(), record.log.len());
+        assert_eq!(
+            replay.result(),
+            GameResultState::Finished(record.reason, record.score)
+        );
+
+        // Starts at the beginning
+        assert_eq!(replay.turn(), 0);
+        assert!(replay.last_action().is_none());
+        assert!(!replay.step_back());
+        assert_eq!(replay.game().hints, config.max_hints);
+
+        // Walk all the way through, checking each action against the record
+        for action in &record.log {
+            assert!(replay.step_forward());
+            assert_eq!(replay.last_action(), Some(action));
+        }
+        assert!(!replay.step_forward());
+        assert_eq!(replay.game().log(), &record.log[..]);
+
+        // And back again
+        assert!(replay.step_back());
+        assert_eq!(replay.game().log(), &record.log[..record.log.len() - 1]);
+
+        replay.seek(5).unwrap();
+        assert_eq!(replay.game().log(), &record.log[..5]);
+        assert!(replay.seek(record.log.len() + 1).is_err());
+    }
+
+    #[test]
+    fn test_replay_from_deck() {
+        let config = GameConfig::standard(2).unwrap();
+        let r1 = Card::new(Color::Red, Number::One);
+        let mut deck = config.generate_deck();
+        let idx = deck.iter().position(|&c| c == r1).unwrap();
+        deck.swap(0, idx);
+
+        // The front of the deck is dealt to player 0's slot 0
+        let moves = vec![
+            HanabiMove::Hint((1, Hint::ColorHint(deck[1].color()))),
+            HanabiMove::Hint((0, Hint::NumberHint(Number::One))),
+            HanabiMove::Play(0),
+        ];
+        let mut replay = Replay::from_deck(config, deck, moves).unwrap();
+        replay.seek(3).unwrap();
+        assert_eq!(replay.game().board, vec![r1]);
+    }
+
+    #[test]
+    fn test_illegal_replay() {
+        let config = GameConfig::standard(2).unwrap();
+        let moves = vec![
+            HanabiMove::Discard(0),
+            HanabiMove::Hint((0, Hint::ColorHint(Color::Red))),
+        ];
+
+        // Can't discard with every hint available
+        assert!(matches!(
+            Replay::from_seed(config.clone(), 0, moves.clone()),
+            Err(HanabiError::IllegalReplayMove { turn: 0, source })
+                if matches!(*source, HanabiError::CannotDiscardAtMaxHints { .. })
+        ));
+
+        // Player 0 can't clue themselves
+        assert!(matches!(
+            Replay::from_seed(config, 0, moves[1..].to_vec()),
+            Err(HanabiError::IllegalReplayMove { turn: 0, source })
+                if matches!(*source, HanabiError::SelfHint(0))
+        ));
+    }
+}
@@ -1,26 +1,283 @@
 use std::collections::VecDeque;
 
-use crate::card::{generate_deck, Card, Number};
-use crate::errors::HanabiError;
+use crate::card::{generate_deck, Card, Color, Number, COLORS, NUMBERS};
+use crate::errors::{HanabiError, Limits};
+use crate::moves::Hint;
+use crate::player::PubID;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Values for the standard game. Everything that reads these at runtime goes through GameConfig.
 pub const MAX_HINTS: u8 = 8;
-pub const NUM_BOMBS: u8 = 3;
+pub const MAX_STRIKES: u8 = 3;
 
 pub const MAX_PLAYERS: u8 = 5;
 pub const MIN_PLAYERS: u8 = 2;
 
+const RAINBOW_SUITS: [Color; 6] = [
+    Color::Red,
+    Color::White,
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+    Color::Rainbow,
+];
+const BLACK_SUITS: [Color; 6] = [
+    Color::Red,
+    Color::White,
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+    Color::Black,
+];
+
+/**
+ * @brief What happens to the points on the board when the team strikes out
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StrikeoutScoring {
+    /**
+     * @brief Official rules: striking out loses every point
+     */
+    #[default]
+    Zero,
+    /**
+     * @brief Common house rule: the team keeps whatever it managed to play
+     */
+    KeepPoints,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameOverReason {
+    /**
+     * @brief Every stack was completed
+     */
+    PerfectScore,
+    /**
+     * @brief The strike limit was reached
+     */
+    StruckOut,
+    /**
+     * @brief The deck ran out and every player had their final turn
+     */
+    DeckExhausted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GameResultState {
-    Finished(usize),
+    Finished(GameOverReason, usize),
     InProgress,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Variant {
+    /**
+     * @brief Five suits, every card touched only by its own color and number
+     */
+    #[default]
+    Standard,
+    /**
+     * @brief A sixth, rainbow suit that every color clue touches. Rainbow itself can't be clued.
+     */
+    Rainbow,
+    /**
+     * @brief A sixth, black suit with only one copy of each number, so every black card is
+     * critical. Only black clues touch it.
+     */
+    Black,
+}
+
+impl Variant {
+    /**
+     * @brief The suits in play, in the order decks are generated
+     */
+    pub fn suits(&self) -> &'static [Color] {
+        match self {
+            Variant::Standard => &COLORS,
+            Variant::Rainbow => &RAINBOW_SUITS,
+            Variant::Black => &BLACK_SUITS,
+        }
+    }
+
+    /**
+     * @brief The colors that can be named in a color clue
+     */
+    pub fn clue_colors(&self) -> &'static [Color] {
+        match self {
+            Variant::Standard | Variant::Rainbow => &COLORS,
+            Variant::Black => &BLACK_SUITS,
+        }
+    }
+
+    /**
+     * @brief Whether a clue of the given color touches cards of the given suit
+     */
+    pub fn color_touches(&self, clue: Color, suit: Color) -> bool {
+        match self {
+            Variant::Standard | Variant::Black => clue == suit,
+            Variant::Rainbow => clue == suit || suit == Color::Rainbow,
+        }
+    }
+
+    /**
+     * @brief Whether the hint touches the given card
+     */
+    pub fn hint_touches(&self, hint: Hint, card: Card) -> bool {
+        match hint {
+            Hint::ColorHint(color) => self.color_touches(color, card.color()),
+            Hint::NumberHint(number) => number == card.number(),
+        }
+    }
+}
+
+/**
+ * @brief Everything that describes which game of hanabi is being played. Use
+ * GameConfig::standard() for the rules in the box, and tweak the fields for anything custom.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameConfig {
+    pub num_players: usize,
+    pub hand_size: usize,
+    pub max_hints: u8,
+    pub max_strikes: u8,
+    pub strikeout_scoring: StrikeoutScoring,
+    /**
+     * @brief Copies of each number in every suit, indexed from One to Five. Some variants override
+     * this for their own suits, see copies_of.
+     */
+    pub card_frequencies: [u8; 5],
+    pub variant: Variant,
+}
+
+impl GameConfig {
+    /**
+     * @brief The standard rules for the given number of players
+     */
+    pub fn standard(num_players: usize) -> Result<Self, HanabiError> {
+        Ok(GameConfig {
+            num_players,
+            hand_size: cards_to_deal(num_players as u8)?.into(),
+            max_hints: MAX_HINTS,
+            max_strikes: MAX_STRIKES,
+            strikeout_scoring: StrikeoutScoring::default(),
+            card_frequencies: [
+                card_frequencies(Number::One),
+                card_frequencies(Number::Two),
+                card_frequencies(Number::Three),
+                card_frequencies(Number::Four),
+                card_frequencies(Number::Five),
+            ],
+            variant: Variant::Standard,
+        })
+    }
+
+    /**
+     * @brief Checks that a game can actually be played with this config
+     */
+    pub fn validate(&self) -> Result<(), HanabiError> {
+        if self.num_players < MIN_PLAYERS.into() || self.num_players > PubID::MAX.into() {
+            return Err(HanabiError::InvalidPlayerCount {
+                num_players: self.num_players,
+                limits: Limits {
+                    lo: MIN_PLAYERS.into(),
+                    hi: PubID::MAX.into(),
+                },
+            });
+        }
+        if self.hand_size == 0 {
+            return Err(HanabiError::EmptyHands);
+        }
+        if self.max_strikes == 0 {
+            return Err(HanabiError::NoStrikesAllowed);
+        }
+        if self.deck_size() < self.hand_size * self.num_players {
+            return Err(HanabiError::DeckTooSmall {
+                deck_len: self.deck_size(),
+                needed: self.hand_size * self.num_players,
+            });
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @brief Copies of the given card in the deck. Black is always one of each.
+     */
+    pub fn copies_of(&self, color: Color, num: Number) -> u8 {
+        match color {
+            Color::Black => 1,
+            _ => self.card_frequencies[number_index(num)],
+        }
+    }
+
+    pub fn deck_size(&self) -> usize {
+        self.variant
+            .suits()
+            .iter()
+            .flat_map(|&color| NUMBERS.iter().map(move |&num| (color, num)))
+            .map(|(color, num)| self.copies_of(color, num) as usize)
+            .sum()
+    }
+
+    /**
+     * @brief Highest score reachable in one suit: a stack stops at the first number with no
+     * copies in the deck
+     */
+    pub fn suit_max_score(&self, suit: Color) -> usize {
+        NUMBERS
+            .iter()
+            .take_while(|&&num| self.copies_of(suit, num) > 0)
+            .count()
+    }
+
+    /**
+     * @brief Score for completing every suit as far as the deck allows
+     */
+    pub fn max_score(&self) -> usize {
+        self.variant
+            .suits()
+            .iter()
+            .map(|&suit| self.suit_max_score(suit))
+            .sum()
+    }
+
+    /**
+     * @brief Generate an (unshuffled) deck holding exactly the cards this config calls for
+     */
+    pub fn generate_deck(&self) -> VecDeque<Card> {
+        generate_deck(self.variant.suits(), |color, num| {
+            self.copies_of(*color, *num)
+        })
+    }
+}
+
+fn number_index(num: Number) -> usize {
+    match num {
+        Number::One => 0,
+        Number::Two => 1,
+        Number::Three => 2,
+        Number::Four => 3,
+        Number::Five => 4,
+    }
+}
+
 pub fn cards_to_deal(num_players: u8) -> Result<u8, HanabiError> {
     match num_players {
         2 | 3 => Ok(5),
         4 | 5 => Ok(4),
-        _ => Err(HanabiError::LogicError(
-            "Invalid number of players".to_string(),
-        )),
+        _ => Err(HanabiError::InvalidPlayerCount {
+            num_players: num_players.into(),
+            limits: Limits {
+                lo: MIN_PLAYERS.into(),
+                hi: MAX_PLAYERS.into(),
+            },
+        }),
     }
 }
 
@@ -44,6 +301,57 @@ pub fn number_below(num: Number) -> Option<Number> {
     }
 }
 
+/**
+ * @brief Checks whether a card of the given color and number could be played on the board right
+ * now: the highest number of its suit must be the number just below it (if any)
+ */
+pub fn playable_on(board: &[Card], color: Color, number: Number) -> bool {
+    let highest_current_number = board
+        .iter()
+        .filter(|c| c.color() == color)
+        .map(|c| c.number())
+        .max();
+
+    number_below(number) == highest_current_number
+}
+
+/**
+ * @brief Whether the game is over, and the score if so. Everything that decides it is public,
+ * so Game and PlayerView both answer from here.
+ */
+pub fn game_result(
+    config: &GameConfig,
+    board: &[Card],
+    strikes: u8,
+    turns_since_last_pickup: Option<usize>,
+) -> GameResultState {
+    // You win -- completed every suit, as far as each one goes
+    if config.max_score() == board.len() {
+        return GameResultState::Finished(GameOverReason::PerfectScore, config.max_score());
+    }
+    // You lose --
+    if strikes >= config.max_strikes {
+        let score = match config.strikeout_scoring {
+            StrikeoutScoring::Zero => 0,
+            StrikeoutScoring::KeepPoints => board.len(),
+        };
+        return GameResultState::Finished(GameOverReason::StruckOut, score);
+    }
+    // You ran out of turns
+    if Some(config.num_players) == turns_since_last_pickup {
+        return GameResultState::Finished(GameOverReason::DeckExhausted, board.len());
+    }
+
+    // TODO: additional condition
+    // there's no way to get any more points given the cards on the board and what is in the
+    // discard pile
+    // if no_way_to_win() {
+    //     return GameResultState::Lose;
+    // }
+
+    GameResultState::InProgress
+}
+
 pub fn generate_normal_deck() -> VecDeque<Card> {
-    generate_deck(|num| card_frequencies(*num))
+    generate_deck(&COLORS, |_, num| card_frequencies(*num))
 }
@@ -0,0 +1,318 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::agent::{play_out, Agent, GameRecord};
+use crate::errors::HanabiError;
+use crate::game::Game;
+use crate::rules::{GameConfig, GameOverReason};
+
+/**
+ * @brief Outcome of one simulated game, with the seed needed to play it again
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSummary {
+    pub seed: u64,
+    pub reason: GameOverReason,
+    pub score: usize,
+}
+
+/**
+ * @brief Score statistics over a batch of games
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    // Every game, in seed order
+    pub games: Vec<GameSummary>,
+
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub perfect_rate: f64,
+    pub strikeout_rate: f64,
+    // histogram[score] is the number of games that ended with that score
+    pub histogram: Vec<usize>,
+}
+
+impl SimulationReport {
+    pub fn from_games(games: Vec<GameSummary>, max_score: usize) -> Self {
+        let mut histogram = vec![0; max_score + 1];
+        for game in &games {
+            if game.score >= histogram.len() {
+                histogram.resize(game.score + 1, 0);
+            }
+            histogram[game.score] += 1;
+        }
+
+        if games.is_empty() {
+            return SimulationReport {
+                games,
+                mean: 0.0,
+                median: 0.0,
+                stddev: 0.0,
+                perfect_rate: 0.0,
+                strikeout_rate: 0.0,
+                histogram,
+            };
+        }
+
+        let n = games.len() as f64;
+        let mean = games.iter().map(|g| g.score as f64).sum::<f64>() / n;
+        let variance = games
+            .iter()
+            .map(|g| (g.score as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        let mut scores: Vec<usize> = games.iter().map(|g| g.score).collect();
+        scores.sort_unstable();
+        let mid = scores.len() / 2;
+        let median = if scores.len().is_multiple_of(2) {
+            (scores[mid - 1] + scores[mid]) as f64 / 2.0
+        } else {
+            scores[mid] as f64
+        };
+
+        let rate_of =
+            |reason: GameOverReason| games.iter().filter(|g| g.reason == reason).count() as f64 / n;
+        let perfect_rate = rate_of(GameOverReason::PerfectScore);
+        let strikeout_rate = rate_of(GameOverReason::StruckOut);
+
+        SimulationReport {
+            games,
+            mean,
+            median,
+            stddev: variance.sqrt(),
+            perfect_rate,
+            strikeout_rate,
+            histogram,
+        }
+    }
+
+    /**
+     * @brief The lowest scoring game, handy for digging into what went wrong
+     */
+    pub fn worst(&self) -> Option<&GameSummary> {
+        self.games.iter().min_by_key(|g| g.score)
+    }
+}
+
+/**
+ * @brief Play a single game dealt from the given seed. Calling this with the seed from a
+ * GameSummary (and the same agents) replays that game exactly.
+ */
+pub fn play_seed<A: Agent + ?Sized>(
+    config: &GameConfig,
+    seed: u64,
+    agents: &mut [Box<A>],
+) -> Result<GameRecord, HanabiError> {
+    let game = Game::from_seed(config.clone(), config.generate_deck(), seed)?;
+    play_out(game, agents)
+}
+
+/**
+ * @brief Play num_games games, seeded first_seed, first_seed + 1, ... make_agents is given each
+ * game's seed and builds a fresh table of agents for it, so seeded bots stay reproducible too.
+ */
+pub fn simulate<F>(
+    config: &GameConfig,
+    num_games: usize,
+    first_seed: u64,
+    mut make_agents: F,
+) -> Result<SimulationReport, HanabiError>
+where
+    F: FnMut(u64) -> Vec<Box<dyn Agent>>,
+{
+    let mut games = Vec::with_capacity(num_games);
+
+    for i in 0..num_games {
+        let seed = first_seed.wrapping_add(i as u64);
+        games.push(summarize(config, seed, &mut make_agents(seed))?);
+    }
+
+    Ok(SimulationReport::from_games(games, config.max_score()))
+}
+
+/**
+ * @brief Same as simulate, but spreads the games over num_threads worker threads (0 means one
+ * per CPU core). Every game depends only on its seed, so the report is identical to simulate's
+ * whatever the thread count. Agents are built on the thread that plays them.
+ */
+pub fn simulate_parallel<F>(
+    config: &GameConfig,
+    num_games: usize,
+    first_seed: u64,
+    num_threads: usize,
+    make_agents: F,
+) -> Result<SimulationReport, HanabiError>
+where
+    F: Fn(u64) -> Vec<Box<dyn Agent>> + Sync,
+{
+    let num_threads = match num_threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+
+    // Workers claim games one at a time, so slow games don't hold up a whole chunk
+    let next_game = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<GameSummary, HanabiError>>>> =
+        Mutex::new((0..num_games).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..num_threads.min(num_games) {
+            scope.spawn(|| loop {
+                let i = next_game.fetch_add(1, Ordering::Relaxed);
+                if i >= num_games {
+                    break;
+                }
+
+                let seed = first_seed.wrapping_add(i as u64);
+                let result = summarize(config, seed, &mut make_agents(seed));
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    // Report the first failing game in seed order, not whichever thread failed first
+    let games = results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every game is played by some worker"))
+        .collect::<Result<Vec<GameSummary>, HanabiError>>()?;
+
+    Ok(SimulationReport::from_games(games, config.max_score()))
+}
+
+fn summarize(
+    config: &GameConfig,
+    seed: u64,
+    agents: &mut [Box<dyn Agent>],
+) -> Result<GameSummary, HanabiError> {
+    let record = play_seed(config, seed, agents)?;
+
+    Ok(GameSummary {
+        seed,
+        reason: record.reason,
+        score: record.score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::{RandomBot, SafeBot};
+
+    fn summary(seed: u64, reason: GameOverReason, score: usize) -> GameSummary {
+        GameSummary {
+            seed,
+            reason,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_report_stats() {
+        let report = SimulationReport::from_games(
+            vec![
+                summary(0, GameOverReason::DeckExhausted, 10),
+                summary(1, GameOverReason::PerfectScore, 25),
+                summary(2, GameOverReason::StruckOut, 0),
+                summary(3, GameOverReason::DeckExhausted, 17),
+            ],
+            25,
+        );
+
+        assert_eq!(report.mean, 13.0);
+        assert_eq!(report.median, 13.5);
+        assert!((report.stddev - 9.1924).abs() < 1e-3);
+        assert_eq!(report.perfect_rate, 0.25);
+        assert_eq!(report.strikeout_rate, 0.25);
+        assert_eq!(report.histogram.len(), 26);
+        assert_eq!(report.histogram[10], 1);
+        assert_eq!(report.histogram.iter().sum::<usize>(), 4);
+        assert_eq!(report.worst().unwrap().seed, 2);
+
+        let empty = SimulationReport::from_games(Vec::new(), 25);
+        assert_eq!(empty.mean, 0.0);
+        assert!(empty.worst().is_none());
+    }
+
+    #[test]
+    fn test_simulate() {
+        let config = GameConfig::standard(3).unwrap();
+        let random_table = |seed: u64| -> Vec<Box<dyn Agent>> {
+            (0..3)
+                .map(|p| Box::new(RandomBot::new(seed * 3 + p)) as Box<dyn Agent>)
+                .collect()
+        };
+
+        let report = simulate(&config, 10, 100, random_table).unwrap();
+        assert_eq!(report.games.len(), 10);
+        assert_eq!(report, simulate(&config, 10, 100, random_table).unwrap());
+
+        // Any game can be reproduced from its seed
+        let game = report.games[7];
+        assert_eq!(game.seed, 107);
+        let record = play_seed(&config, game.seed, &mut random_table(game.seed)).unwrap();
+        assert_eq!((record.reason, record.score), (game.reason, game.score));
+
+        let safe = simulate(&config, 10, 0, |_| {
+            (0..3)
+                .map(|_| Box::new(SafeBot::new()) as Box<dyn Agent>)
+                .collect()
+        })
+        .unwrap();
+        assert_eq!(safe.strikeout_rate, 0.0);
+        assert!(safe.mean > report.mean);
+    }
+
+    #[test]
+    fn test_simulate_custom_deck() {
+        // Without any fives, no stack can go past 4
+        let mut config = GameConfig::standard(3).unwrap();
+        config.card_frequencies = [3, 2, 2, 2, 0];
+        assert_eq!(config.max_score(), 20);
+
+        let report = simulate(&config, 10, 0, |_| {
+            (0..3)
+                .map(|_| Box::new(SafeBot::new()) as Box<dyn Agent>)
+                .collect()
+        })
+        .unwrap();
+        assert_eq!(report.histogram.len(), 21);
+        assert!(report.games.iter().all(|game| game.score <= 20));
+    }
+
+    #[test]
+    fn test_simulate_parallel() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Game>();
+        assert_send::<GameRecord>();
+
+        let config = GameConfig::standard(4).unwrap();
+        let random_table = |seed: u64| -> Vec<Box<dyn Agent>> {
+            (0..4)
+                .map(|p| Box::new(RandomBot::new(seed * 4 + p)) as Box<dyn Agent>)
+                .collect()
+        };
+
+        let expected = simulate(&config, 50, 7, random_table).unwrap();
+        for num_threads in &[0, 1, 3, 8, 64] {
+            let report = simulate_parallel(&config, 50, 7, *num_threads, random_table).unwrap();
+            assert_eq!(report, expected);
+        }
+
+        let empty = simulate_parallel(&config, 0, 0, 4, random_table).unwrap();
+        assert!(empty.games.is_empty());
+
+        // Every table is the wrong size, so the first game's error is the one reported
+        assert!(matches!(
+            simulate_parallel(&config, 10, 0, 4, |_| Vec::new()),
+            Err(HanabiError::WrongNumberOfAgents {
+                agents: 0,
+                num_players: 4
+            })
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::card::{Card, CardKnowledge, CardView};
+use crate::moves::{hints_for, Action, HanabiMove};
+use crate::player::PubID;
+use crate::rules::{game_result, GameConfig, GameResultState};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * @brief Another player's hand, as seen from across the table: the cards themselves, plus what
+ * that player has been told about them (hints are given out loud, so everyone knows).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OtherHand {
+    pub public_id: PubID,
+    pub cards: Vec<CardView>,
+    pub knowledge: Vec<CardKnowledge>,
+}
+
+/**
+ * @brief An owned snapshot of everything one player may legally know about the game. It holds
+ * no reference back into the Game, so it can be handed to a bot without letting it peek at its
+ * own cards or the deck.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayerView {
+    pub public_id: PubID,
+    pub config: GameConfig,
+
+    // Hands
+    pub my_knowledge: Vec<CardKnowledge>,
+    pub others: Vec<OtherHand>,
+
+    // Stacks of cards
+    pub board: Vec<Card>,
+    pub discard: Vec<Card>,
+    pub deck_len: usize,
+
+    // Counters
+    pub hints: u8,
+    pub strikes: u8,
+    pub active_player: PubID,
+    pub turns_since_last_pickup: Option<usize>,
+
+    // Every action so far, with the cards this player drew hidden
+    pub log: Vec<Action>,
+}
+
+impl PlayerView {
+    pub fn is_my_turn(&self) -> bool {
+        self.public_id == self.active_player
+    }
+
+    pub fn hand_len(&self) -> usize {
+        self.my_knowledge.len()
+    }
+
+    pub fn other(&self, public_id: PubID) -> Option<&OtherHand> {
+        self.others.iter().find(|hand| hand.public_id == public_id)
+    }
+
+    /**
+     * @brief How the game stands, as far as anyone at the table can tell (which is all of it)
+     */
+    pub fn finished(&self) -> GameResultState {
+        game_result(
+            &self.config,
+            &self.board,
+            self.strikes,
+            self.turns_since_last_pickup,
+        )
+    }
+
+    /**
+     * @brief Every move this player could make right now. Empty if it isn't this player's turn
+     * or the game is over.
+     *
+     * Plays come first (by slot), then discards (by slot) if a hint can be regained, then for
+     * each other player every color and number hint that touches at least one of their cards.
+     */
+    pub fn legal_moves(&self) -> Vec<HanabiMove> {
+        if !self.is_my_turn() {
+            return Vec::new();
+        }
+        if let GameResultState::Finished(..) = self.finished() {
+            return Vec::new();
+        }
+
+        let mut moves: Vec<HanabiMove> = (0..self.hand_len()).map(HanabiMove::Play).collect();
+
+        if self.hints < self.config.max_hints {
+            moves.extend((0..self.hand_len()).map(HanabiMove::Discard));
+        }
+
+        if self.hints > 0 {
+            for other in &self.others {
+                moves.extend(hints_for(
+                    other.public_id,
+                    &other.cards,
+                    self.config.variant,
+                ));
+            }
+        }
+
+        moves
+    }
+}
+
+/**
+ * @brief A player's hand as a referee sees it: every true card next to what its holder knows
+ * about it.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpectatorHand {
+    pub public_id: PubID,
+    pub cards: Vec<(Card, CardKnowledge)>,
+}
+
+/**
+ * @brief Omniscient, read-only snapshot of the whole game for referees, replay viewers and
+ * overlays. This is deliberately a different type from PlayerView: nothing that expects a
+ * PlayerView (such as a bot) can be handed one of these.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpectatorView {
+    pub config: GameConfig,
+
+    // Hands
+    pub hands: Vec<SpectatorHand>,
+
+    // Stacks of cards
+    pub board: Vec<Card>,
+    pub discard: Vec<Card>,
+    pub deck_len: usize,
+    // Remaining deck in draw order, only if it was asked to be revealed
+    pub deck: Option<Vec<Card>>,
+
+    // Counters
+    pub hints: u8,
+    pub strikes: u8,
+    pub active_player: PubID,
+    pub turns_since_last_pickup: Option<usize>,
+
+    // Every action so far, nothing hidden
+    pub log: Vec<Action>,
+}